@@ -0,0 +1,503 @@
+//! Async shard reading and decoding (`tokio` feature)
+//!
+//! Mirrors `ShardReader` and the decoders in `erasure` for async servers, so
+//! decoding does not need `spawn_blocking`. Shard reads of a block are issued
+//! concurrently; blocks are decoded one at a time in object order.
+
+use std::future::Future;
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use futures_util::future::join_all;
+use futures_util::stream::{self, Stream};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::erasure::{
+    block_spans, clamp_range, part_path, BlockSpan, PartState, PendingFetches, ShardChecksums,
+    ShardFetch,
+};
+use crate::layout::ShardLayout;
+use crate::types::ObjectMeta;
+
+/// Async counterpart of `ShardReader`.
+pub trait AsyncShardReader: Sync {
+    /// Read a shard file for the given disk, returning its full contents.
+    /// Returns Ok(None) if the shard is missing (disk unavailable, file not found).
+    fn read_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    /// Read the xl.meta file of an object on the given disk.
+    /// Returns Ok(None) if it is missing. The default reports every xl.meta as missing.
+    fn read_xlmeta(
+        &self,
+        _disk_index: usize,
+        _bucket: &str,
+        _key: &str,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Read up to `len` bytes of a shard file starting at `offset`, like
+    /// `ShardReader::read_shard_range`. The default reads the whole shard.
+    #[allow(clippy::too_many_arguments)]
+    fn read_shard_range(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send {
+        async move {
+            let data = self
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+                .await?;
+            Ok(data.map(|data| {
+                let (start, len) = clamp_range(data.len() as u64, offset, len);
+                data[start as usize..start as usize + len].to_vec()
+            }))
+        }
+    }
+
+    /// Length of a shard file in bytes. Returns Ok(None) if the shard is missing.
+    /// The default reads the whole shard.
+    fn shard_len(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> impl Future<Output = Result<Option<u64>>> + Send {
+        async move {
+            let data = self
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+                .await?;
+            Ok(data.map(|data| data.len() as u64))
+        }
+    }
+}
+
+/// Filesystem shard reader on `tokio::fs`, equivalent to `FsShardReader`
+pub struct TokioFsShardReader {
+    pub disk_paths: Vec<String>,
+}
+
+/// Map NotFound to Ok(None)
+fn not_found_as_none<T>(result: std::io::Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl AsyncShardReader for TokioFsShardReader {
+    async fn read_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        not_found_as_none(tokio::fs::read(&path).await)
+    }
+
+    async fn read_xlmeta(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(disk) = self.disk_paths.get(disk_index).filter(|p| !p.is_empty()) else {
+            return Ok(None);
+        };
+        let path = format!("{}/{}/{}/xl.meta", disk, bucket, key);
+        not_found_as_none(tokio::fs::read(&path).await)
+    }
+
+    async fn read_shard_range(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        let Some(mut file) = not_found_as_none(tokio::fs::File::open(&path).await)? else {
+            return Ok(None);
+        };
+        let (start, len) = clamp_range(file.metadata().await?.len(), offset, len);
+        let mut buf = vec![0u8; len];
+        file.seek(std::io::SeekFrom::Start(start)).await?;
+        file.read_exact(&mut buf)
+            .await
+            .with_context(|| format!("read {} bytes at {} of {}", len, start, path))?;
+        Ok(Some(buf))
+    }
+
+    async fn shard_len(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<u64>> {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        let md = not_found_as_none(tokio::fs::metadata(&path).await)?;
+        Ok(md.map(|md| md.len()))
+    }
+}
+
+/// Decode a whole object. Async counterpart of `decode_object`.
+pub async fn decode_object_async<R: AsyncShardReader + ?Sized>(
+    reader: &R,
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+) -> Result<Vec<u8>> {
+    let mut result = Vec::with_capacity(meta.size as usize);
+    let mut blocks = AsyncBlocks::new(reader, meta, skip_disks, block_spans(meta, 0, meta.size));
+    while let Some(block) = blocks.next_block().await {
+        result.extend_from_slice(&block?);
+    }
+    Ok(result)
+}
+
+/// Decode `len` bytes of an object starting at `offset`, clipped to the
+/// object size. Async counterpart of `decode_object_range`.
+pub async fn decode_object_range_async<R: AsyncShardReader + ?Sized>(
+    reader: &R,
+    meta: &ObjectMeta,
+    offset: i64,
+    len: i64,
+    skip_disks: &[usize],
+) -> Result<Vec<u8>> {
+    if offset < 0 || len < 0 {
+        bail!("invalid range: offset {}, length {}", offset, len);
+    }
+    if offset > meta.size {
+        bail!("range start {} beyond object size {}", offset, meta.size);
+    }
+    let end = offset + std::cmp::min(len, meta.size - offset);
+    let mut result = Vec::with_capacity((end - offset) as usize);
+    let mut blocks = AsyncBlocks::new(reader, meta, skip_disks, block_spans(meta, offset, end));
+    while let Some(block) = blocks.next_block().await {
+        result.extend_from_slice(&block?);
+    }
+    Ok(result)
+}
+
+/// Stream an object as decoded blocks, in order.
+///
+/// Only one block is decoded at a time, so memory stays bounded by a block
+/// of every shard. The stream ends after the first error.
+pub fn decode_object_stream<'a, R: AsyncShardReader + ?Sized>(
+    reader: &'a R,
+    meta: &'a ObjectMeta,
+    skip_disks: &'a [usize],
+) -> impl Stream<Item = Result<Bytes>> + Send + 'a {
+    let blocks = AsyncBlocks::new(reader, meta, skip_disks, block_spans(meta, 0, meta.size));
+    stream::unfold(blocks, |mut blocks| async move {
+        let block = blocks.next_block().await?;
+        if block.is_err() {
+            blocks.spans.clear();
+        }
+        Some((block, blocks))
+    })
+}
+
+/// Block-by-block decoder over a list of spans
+struct AsyncBlocks<'a, R: ?Sized> {
+    reader: &'a R,
+    meta: &'a ObjectMeta,
+    skip_disks: &'a [usize],
+    // Remaining spans, in reverse order
+    spans: Vec<BlockSpan>,
+    // Whole-file bitrot checksums, read with the first block
+    checksums: Option<ShardChecksums>,
+    // Index into meta.parts and state of the current part
+    part: Option<(usize, PartState<'a>)>,
+}
+
+impl<'a, R: AsyncShardReader + ?Sized> AsyncBlocks<'a, R> {
+    fn new(
+        reader: &'a R,
+        meta: &'a ObjectMeta,
+        skip_disks: &'a [usize],
+        mut spans: Vec<BlockSpan>,
+    ) -> Self {
+        spans.reverse();
+        AsyncBlocks {
+            reader,
+            meta,
+            skip_disks,
+            spans,
+            checksums: None,
+            part: None,
+        }
+    }
+
+    /// Decode the next block, trimmed to its span
+    async fn next_block(&mut self) -> Option<Result<Bytes>> {
+        let span = self.spans.pop()?;
+        let part = &self.meta.parts[span.part];
+        let decoded = self
+            .decode(span)
+            .await
+            .with_context(|| format!("decode part {} block {}", part.number, span.block));
+        Some(decoded.and_then(|data| {
+            if data.len() < span.end {
+                bail!("decoded block shorter than expected");
+            }
+            Ok(Bytes::from(data).slice(span.start..span.end))
+        }))
+    }
+
+    async fn decode(&mut self, span: BlockSpan) -> Result<Vec<u8>> {
+        let (reader, meta, skip_disks) = (self.reader, self.meta, self.skip_disks);
+        if !matches!(&self.part, Some((i, _)) if *i == span.part) {
+            if self.checksums.is_none() {
+                self.checksums = Some(read_checksums(reader, meta, skip_disks).await);
+            }
+            let part = &meta.parts[span.part];
+            let checksums = self.checksums.as_ref().map(|c| c.part(part.number));
+            let checksums = checksums.unwrap_or_default();
+            let layout = ShardLayout::for_part_size(meta, part.size);
+            let state = PartState::new(meta, part.number, skip_disks, Some(layout), checksums);
+            self.part = Some((span.part, state));
+        }
+        let Some((_, state)) = &mut self.part else {
+            bail!("part state missing");
+        };
+
+        // Read only data shards first; parity only if the block needs it
+        let shard_size = meta.shard_size();
+        let mut out = Vec::new();
+        let mut pending = state.pending(0..meta.data_blocks, span.block);
+        loop {
+            fetch_all(reader, state, pending).await;
+            match state.decode(span.block, shard_size, &mut out, None)? {
+                Some(more) => pending = more,
+                None => return Ok(out),
+            }
+        }
+    }
+}
+
+/// Async `ShardChecksums::read`
+async fn read_checksums<R: AsyncShardReader + ?Sized>(
+    reader: &R,
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+) -> ShardChecksums {
+    let mut checksums = ShardChecksums::own(meta);
+    for disk_idx in checksums.missing_disks(meta, skip_disks) {
+        if let Ok(Some(xl)) = reader.read_xlmeta(disk_idx, &meta.bucket, &meta.key).await {
+            checksums.add_copy(meta, disk_idx, &xl);
+        }
+    }
+    checksums
+}
+
+/// Fetch shards concurrently and record them in `state`
+async fn fetch_all<R: AsyncShardReader + ?Sized>(
+    reader: &R,
+    state: &mut PartState<'_>,
+    pending: PendingFetches,
+) {
+    let meta = state.meta();
+    let (data_dir, part_number) = (state.data_dir(), state.part_number());
+    let fetches = pending.iter().map(|&(_, disk_idx, fetch)| async move {
+        let data = match fetch {
+            ShardFetch::Whole => {
+                reader
+                    .read_shard(disk_idx, &meta.bucket, &meta.key, data_dir, part_number)
+                    .await
+            }
+            ShardFetch::Range { offset, len } => {
+                reader
+                    .read_shard_range(
+                        disk_idx,
+                        &meta.bucket,
+                        &meta.key,
+                        data_dir,
+                        part_number,
+                        offset,
+                        len,
+                    )
+                    .await
+            }
+        };
+        data
+    });
+    let results = join_all(fetches).await;
+    for ((shard_idx, _, _), data) in pending.into_iter().zip(results) {
+        state.store(shard_idx, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::erasure::{decode_object, ShardReader};
+    use crate::testutil::{corpus_meta, corpus_reader, multipart_object, PartsMockReader};
+
+    /// Async view of a sync mock, with the default range implementation
+    struct AsyncMock(PartsMockReader);
+
+    impl AsyncShardReader for AsyncMock {
+        async fn read_shard(
+            &self,
+            disk_index: usize,
+            bucket: &str,
+            key: &str,
+            data_dir: &str,
+            part_number: i32,
+        ) -> Result<Option<Vec<u8>>> {
+            self.0
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+        }
+    }
+
+    fn tokio_corpus_reader() -> TokioFsShardReader {
+        TokioFsShardReader {
+            disk_paths: corpus_reader().disk_paths,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_object_async_matches_sync() {
+        let reader = tokio_corpus_reader();
+        let meta = corpus_meta();
+        let full = decode_object(&corpus_reader(), &meta, &[]).unwrap();
+        for skip in [&[][..], &[0, 1], &[3, 4]] {
+            let data = decode_object_async(&reader, &meta, skip).await.unwrap();
+            assert!(data == full, "skip {:?}", skip);
+        }
+        assert!(decode_object_async(&reader, &meta, &[0, 1, 2])
+            .await
+            .is_err());
+
+        let (data, meta, mock) = multipart_object();
+        let mock = AsyncMock(mock);
+        assert_eq!(decode_object_async(&mock, &meta, &[2]).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_decode_object_range_async() {
+        let (data, meta, mock) = multipart_object();
+        let mock = AsyncMock(mock);
+        for (offset, len) in [(5, 10), (250, 120), (650, 100)] {
+            let got = decode_object_range_async(&mock, &meta, offset as i64, len as i64, &[0])
+                .await
+                .unwrap();
+            let end = std::cmp::min(offset + len, data.len());
+            assert_eq!(got, &data[offset..end]);
+        }
+        assert!(decode_object_range_async(&mock, &meta, 701, 1, &[])
+            .await
+            .is_err());
+
+        let reader = tokio_corpus_reader();
+        let meta = corpus_meta();
+        let full = decode_object(&corpus_reader(), &meta, &[]).unwrap();
+        let got = decode_object_range_async(&reader, &meta, 600000, 50000, &[1])
+            .await
+            .unwrap();
+        assert!(got == full[600000..]);
+    }
+
+    #[tokio::test]
+    async fn test_decode_object_stream_yields_blocks_in_order() {
+        let (data, meta, mock) = multipart_object();
+        let mock = AsyncMock(mock);
+        let blocks: Vec<Bytes> = decode_object_stream(&mock, &meta, &[1])
+            .map(|b| b.unwrap())
+            .collect()
+            .await;
+        // 300 + 400 bytes in 64-byte blocks
+        assert_eq!(blocks.len(), 5 + 7);
+        assert_eq!(blocks.concat(), data);
+
+        let reader = tokio_corpus_reader();
+        let meta = corpus_meta();
+        let results: Vec<_> = decode_object_stream(&reader, &meta, &[0, 1, 2])
+            .collect()
+            .await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tokio_fs_reader_matches_fs_reader() {
+        let fs = corpus_reader();
+        let reader = tokio_corpus_reader();
+        let meta = corpus_meta();
+        let data_dir = meta.data_dir_string();
+
+        let full = fs
+            .read_shard(1, "bucket", "testobj", &data_dir, 1)
+            .unwrap()
+            .unwrap();
+        let range = reader
+            .read_shard_range(1, "bucket", "testobj", &data_dir, 1, 10, 100)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(range, &full[10..110]);
+        let len = reader.shard_len(1, "bucket", "testobj", &data_dir, 1).await;
+        assert_eq!(len.unwrap(), Some(full.len() as u64));
+        let missing = reader
+            .read_shard(1, "bucket", "missing", &data_dir, 1)
+            .await;
+        assert!(missing.unwrap().is_none());
+        assert!(reader
+            .read_xlmeta(1, "bucket", "testobj")
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,98 @@
+//! Per-disk diagnostics collected while decoding
+//!
+//! Records, for every shard block that could not be used as-is, which disk
+//! it lives on and why, so heal decisions and disk replacement can be based
+//! on what decode actually saw.
+
+/// Why a shard block could not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardIssue {
+    /// The caller asked to skip the disk
+    Skipped,
+    /// No shard file (file not found, disk not configured, or no disk holds this shard)
+    Missing,
+    /// Reading the shard failed with an I/O error
+    Unreadable(String),
+    /// Block or whole-file hash mismatch, or the block is missing from a short file
+    BitrotFailed(String),
+}
+
+/// One shard block that decode could not read cleanly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardDiagnostic {
+    /// Disk holding the shard (None if the distribution has no disk for it)
+    pub disk_index: Option<usize>,
+    /// 1-based shard index, as in the distribution
+    pub erasure_index: usize,
+    pub part_number: i32,
+    pub block: usize,
+    pub issue: ShardIssue,
+    /// True if this was a data shard and its block was rebuilt from parity
+    pub reconstructed: bool,
+}
+
+/// Everything decode could not read cleanly, in the order it was found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeReport {
+    pub shards: Vec<ShardDiagnostic>,
+}
+
+impl DecodeReport {
+    /// True if every shard block read was present and intact
+    pub fn is_clean(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    /// Diagnostics of one disk
+    pub fn for_disk(&self, disk_index: usize) -> impl Iterator<Item = &ShardDiagnostic> {
+        self.shards
+            .iter()
+            .filter(move |d| d.disk_index == Some(disk_index))
+    }
+
+    /// Disks with at least one missing, unreadable or corrupt block, sorted.
+    /// Disks that were only skipped are not included.
+    pub fn failing_disks(&self) -> Vec<usize> {
+        let mut disks: Vec<usize> = self
+            .shards
+            .iter()
+            .filter(|d| d.issue != ShardIssue::Skipped)
+            .filter_map(|d| d.disk_index)
+            .collect();
+        disks.sort_unstable();
+        disks.dedup();
+        disks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(disk_index: usize, issue: ShardIssue) -> ShardDiagnostic {
+        ShardDiagnostic {
+            disk_index: Some(disk_index),
+            erasure_index: disk_index + 1,
+            part_number: 1,
+            block: 0,
+            issue,
+            reconstructed: false,
+        }
+    }
+
+    #[test]
+    fn test_failing_disks_ignores_skipped() {
+        let report = DecodeReport {
+            shards: vec![
+                diagnostic(3, ShardIssue::BitrotFailed("hash mismatch".into())),
+                diagnostic(0, ShardIssue::Skipped),
+                diagnostic(1, ShardIssue::Missing),
+                diagnostic(3, ShardIssue::Unreadable("EIO".into())),
+            ],
+        };
+        assert!(!report.is_clean());
+        assert_eq!(report.failing_disks(), vec![1, 3]);
+        assert_eq!(report.for_disk(3).count(), 2);
+        assert!(DecodeReport::default().is_clean());
+    }
+}
@@ -0,0 +1,270 @@
+//! MinIO's key-based placement
+//!
+//! Port of `hashOrder` in cmd/erasure-metadata-utils.go and of the erasure
+//! set hashing in cmd/erasure-sets.go. MinIO derives the distribution of a
+//! new object from the CRC32 of `bucket/object`, and the erasure set holding
+//! it from the object name with the pool's format.json `distributionAlgo`.
+//! Both can be recomputed to validate stored metadata or to find an object's
+//! shards when its xl.meta is lost.
+
+use std::hash::Hasher;
+use std::str::FromStr;
+
+use anyhow::{bail, ensure, Result};
+use siphasher::sip::SipHasher24;
+
+use crate::types::{ClusterConfig, ObjectMeta, PoolConfig, Uuid16};
+
+/// MinIO's `hashOrder`: a rotation of 1..=cardinality starting after
+/// `crc32(key) % cardinality`. Empty for a cardinality of 0. Errors above 255,
+/// as shard indices are stored as bytes.
+pub fn hash_order(key: &str, cardinality: usize) -> Result<Vec<u8>> {
+    ensure!(
+        cardinality <= u8::MAX as usize,
+        "cardinality {} does not fit shard indices",
+        cardinality
+    );
+    if cardinality == 0 {
+        return Ok(Vec::new());
+    }
+    let start = crc32fast::hash(key.as_bytes()) as usize % cardinality;
+    Ok((1..=cardinality)
+        .map(|i| (1 + (start + i) % cardinality) as u8)
+        .collect())
+}
+
+/// Distribution MinIO gives object `key` of `bucket` on a set of `drives`
+/// drives: disk `d` holds shard `distribution[d]` (1-based).
+pub fn object_distribution(bucket: &str, key: &str, drives: usize) -> Result<Vec<u8>> {
+    hash_order(&format!("{}/{}", bucket, key), drives)
+}
+
+/// Whether `meta.distribution` is the one MinIO derives for its bucket and key
+pub fn distribution_matches(meta: &ObjectMeta) -> bool {
+    object_distribution(&meta.bucket, &meta.key, meta.distribution.len())
+        .is_ok_and(|d| d == meta.distribution)
+}
+
+/// Erasure set placement algorithm (`xl.distributionAlgo` in format.json)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DistributionAlgo {
+    /// Legacy CRC32 of the object name (format.json v2)
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "CRCMOD"))]
+    CrcMod,
+    /// SipHash-2-4 of the object name keyed with the deployment id
+    #[cfg_attr(feature = "serde", serde(rename = "SIPMOD"))]
+    SipMod,
+    /// Same placement as SIPMOD; parity also depends on the set size
+    #[cfg_attr(feature = "serde", serde(rename = "SIPMOD+PARITY"))]
+    SipModParity,
+}
+
+impl FromStr for DistributionAlgo {
+    type Err = anyhow::Error;
+
+    /// An empty algorithm is CRCMOD, as for format.json files older than v3
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "" | "CRCMOD" => Ok(DistributionAlgo::CrcMod),
+            "SIPMOD" => Ok(DistributionAlgo::SipMod),
+            "SIPMOD+PARITY" => Ok(DistributionAlgo::SipModParity),
+            _ => bail!("unknown distribution algorithm {:?}", s),
+        }
+    }
+}
+
+/// MinIO's `crcHashMod`: `crc32(key) % cardinality`
+pub fn crc_hash_mod(key: &str, cardinality: usize) -> usize {
+    crc32fast::hash(key.as_bytes()) as usize % cardinality.max(1)
+}
+
+/// MinIO's `sipHashMod`: SipHash-2-4 of `key`, keyed with the deployment id
+/// bytes as two little-endian u64s, modulo `cardinality`
+pub fn sip_hash_mod(key: &str, cardinality: usize, deployment_id: &Uuid16) -> usize {
+    let id = &deployment_id.0;
+    let k0 = u64::from_le_bytes(id[..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(id[8..].try_into().unwrap());
+    let mut hasher = SipHasher24::new_with_keys(k0, k1);
+    hasher.write(key.as_bytes());
+    (hasher.finish() % cardinality.max(1) as u64) as usize
+}
+
+/// Index of the erasure set among `set_count` sets that holds object `key`
+/// (the object name, without the bucket)
+pub fn hash_key(
+    algo: DistributionAlgo,
+    key: &str,
+    set_count: usize,
+    deployment_id: &Uuid16,
+) -> usize {
+    match algo {
+        DistributionAlgo::CrcMod => crc_hash_mod(key, set_count),
+        DistributionAlgo::SipMod | DistributionAlgo::SipModParity => {
+            sip_hash_mod(key, set_count, deployment_id)
+        }
+    }
+}
+
+/// Set of `pool` that holds object `key`, by the pool's distribution algorithm
+pub fn set_index(pool: &PoolConfig, deployment_id: &Uuid16, key: &str) -> Result<usize> {
+    ensure!(
+        !pool.sets.is_empty(),
+        "pool {} has no erasure sets",
+        pool.pool_id
+    );
+    Ok(hash_key(
+        pool.distribution_algo,
+        key,
+        pool.sets.len(),
+        deployment_id,
+    ))
+}
+
+/// `(pool_index, set_index)` of the one set per pool that can hold object `key`.
+///
+/// Within a pool the set is fixed by the key; which pool holds the object is
+/// decided by MinIO at write time, so every pool has a candidate.
+pub fn object_sets(
+    cluster: &ClusterConfig,
+    deployment_id: &Uuid16,
+    key: &str,
+) -> Result<Vec<(usize, usize)>> {
+    cluster
+        .pools
+        .iter()
+        .map(|pool| Ok((pool.pool_index, set_index(pool, deployment_id, key)?)))
+        .collect()
+}
+
+/// Fill `meta.pool_index` and `meta.set_index` for an object found in `pool`
+pub fn place_object(
+    meta: &mut ObjectMeta,
+    pool: &PoolConfig,
+    deployment_id: &Uuid16,
+) -> Result<()> {
+    meta.set_index = set_index(pool, deployment_id, &meta.key)? as i32;
+    meta.pool_index = pool.pool_index as i32;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_order_matches_minio() {
+        // From TestHashOrder in MinIO's cmd/erasure-metadata-utils_test.go
+        let cases = [
+            ("object", 14),
+            ("The Shining Script <v1>.pdf", 16),
+            ("Cost Benefit Analysis (2009-2010).pptx", 15),
+            ("117Gn8rfHL2ACARPAhaFd0AGzic9pUbIA/5OCn5A", 3),
+            ("SHØRT", 11),
+            ("a/b/c/", 3),
+            ("/a/b/c", 6),
+        ];
+        for (key, first) in cases {
+            let want: Vec<u8> = (0..16).map(|i| (first - 1 + i) % 16 + 1).collect();
+            assert_eq!(hash_order(key, 16).unwrap(), want, "{}", key);
+        }
+        assert!(hash_order("object", 0).unwrap().is_empty());
+        assert_eq!(hash_order("object", 255).unwrap().len(), 255);
+        assert!(hash_order("object", 256).is_err());
+    }
+
+    #[test]
+    fn test_distribution_matches() {
+        let mut meta = ObjectMeta {
+            bucket: "bucket".to_string(),
+            key: "a/b".to_string(),
+            distribution: object_distribution("bucket", "a/b", 4).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(meta.distribution, hash_order("bucket/a/b", 4).unwrap());
+        assert!(distribution_matches(&meta));
+        meta.distribution.rotate_left(1);
+        assert!(!distribution_matches(&meta));
+    }
+
+    // Names from MinIO's TestSipHashMod / TestCrcHashMod, with 200 sets
+    const NAMES: [(&str, usize, usize); 8] = [
+        ("object", 37, 28),
+        ("The Shining Script <v1>.pdf", 38, 142),
+        ("Cost Benefit Analysis (2009-2010).pptx", 59, 133),
+        ("117Gn8rfHL2ACARPAhaFd0AGzic9pUbIA/5OCn5A", 35, 185),
+        ("SHØRT", 49, 97),
+        (
+            "There are far too many object names, and far too few bucket names!",
+            8,
+            101,
+        ),
+        ("a/b/c/", 159, 193),
+        ("/a/b/c", 96, 116),
+    ];
+
+    fn test_uuid() -> Uuid16 {
+        "f5c58c61-7175-4018-ab5e-a94fe9c2de4e".parse().unwrap()
+    }
+
+    #[test]
+    fn test_set_hashing_matches_minio() {
+        let id = test_uuid();
+        for (name, sip, crc) in NAMES {
+            assert_eq!(
+                hash_key(DistributionAlgo::SipMod, name, 200, &id),
+                sip,
+                "{}",
+                name
+            );
+            assert_eq!(
+                hash_key(DistributionAlgo::SipModParity, name, 200, &id),
+                sip
+            );
+            assert_eq!(
+                hash_key(DistributionAlgo::CrcMod, name, 200, &id),
+                crc,
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            "".parse::<DistributionAlgo>().unwrap(),
+            DistributionAlgo::CrcMod
+        );
+        assert!("SIPHASH".parse::<DistributionAlgo>().is_err());
+    }
+
+    #[test]
+    fn test_place_object_in_pool() {
+        let pool = |index: usize, algo: DistributionAlgo| PoolConfig {
+            pool_id: format!("pool-{}", index),
+            pool_index: index,
+            sets: vec![Vec::new(); 200],
+            distribution_algo: algo,
+        };
+        let cluster = ClusterConfig {
+            pools: vec![
+                pool(0, DistributionAlgo::SipModParity),
+                pool(1, DistributionAlgo::CrcMod),
+            ],
+        };
+        let id = test_uuid();
+        assert_eq!(
+            object_sets(&cluster, &id, "object").unwrap(),
+            vec![(0, 37), (1, 28)]
+        );
+
+        let mut meta = ObjectMeta {
+            key: "a/b/c/".to_string(),
+            ..Default::default()
+        };
+        place_object(&mut meta, &cluster.pools[1], &id).unwrap();
+        assert_eq!((meta.pool_index, meta.set_index), (1, 193));
+
+        let mut empty = pool(3, DistributionAlgo::SipMod);
+        empty.sets.clear();
+        assert!(set_index(&empty, &id, "object").is_err());
+    }
+}
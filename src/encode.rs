@@ -0,0 +1,246 @@
+//! Erasure encoder writing MinIO's on-disk object layout
+//!
+//! Splits an object into blocks, computes Reed-Solomon parity and produces
+//! each disk's `part.1` (HighwayHash256S bitrot) and xl.meta copy, placed by
+//! MinIO's key-based distribution.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, ensure, Context, Result};
+use md5::{Digest, Md5};
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use sha2::Sha256;
+
+use crate::distribution::object_distribution;
+use crate::erasure::ShardReader;
+use crate::shard::ShardWriter;
+use crate::sink::ShardSink;
+use crate::types::{ceil_div, BitrotAlgorithm, ObjectMeta, PartMeta, Uuid16, VersionType};
+use crate::xlmeta;
+
+/// An object encoded for one erasure set, ready to be written to disks
+#[derive(Debug, Clone)]
+pub struct EncodedObject {
+    /// Object metadata (erasure_index 0). Fields other than the erasure
+    /// layout, such as `mod_time` or `user_meta`, may be changed before writing.
+    pub meta: ObjectMeta,
+    /// `part.1` file of each disk, indexed like `meta.distribution`
+    pub shards: Vec<Vec<u8>>,
+}
+
+impl EncodedObject {
+    /// xl.meta copy of `disk_index`, carrying that disk's EcIndex
+    pub fn xlmeta(&self, disk_index: usize) -> Result<Vec<u8>> {
+        let ec_index = *self
+            .meta
+            .distribution
+            .get(disk_index)
+            .with_context(|| format!("no disk {} in distribution", disk_index))?;
+        let mut meta = self.meta.clone();
+        meta.erasure_index = ec_index as usize;
+        xlmeta::encode(&meta)
+    }
+
+    /// Write every disk's shard file and xl.meta copy, xl.meta last
+    pub fn write_to(&self, sink: &dyn ShardSink) -> Result<()> {
+        let meta = &self.meta;
+        let data_dir = meta.data_dir_string();
+        for (disk, shard) in self.shards.iter().enumerate() {
+            sink.write_shard(disk, &meta.bucket, &meta.key, &data_dir, 1, shard)
+                .with_context(|| format!("write part 1 to disk {}", disk))?;
+        }
+        for disk in 0..self.shards.len() {
+            sink.write_xlmeta(disk, &meta.bucket, &meta.key, &self.xlmeta(disk)?)
+                .with_context(|| format!("write xl.meta to disk {}", disk))?;
+        }
+        Ok(())
+    }
+}
+
+impl ShardReader for EncodedObject {
+    fn read_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<Vec<u8>>> {
+        if bucket != self.meta.bucket
+            || key != self.meta.key
+            || data_dir != self.meta.data_dir_string()
+            || part_number != 1
+        {
+            return Ok(None);
+        }
+        Ok(self.shards.get(disk_index).cloned())
+    }
+
+    fn read_xlmeta(&self, disk_index: usize, bucket: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        if bucket != self.meta.bucket || key != self.meta.key || disk_index >= self.shards.len() {
+            return Ok(None);
+        }
+        self.xlmeta(disk_index).map(Some)
+    }
+}
+
+/// Erasure-code `data` as a single-part object on `ec_m + ec_n` disks.
+///
+/// `ec_m` data and `ec_n` parity shards per block of `block_size` bytes, with
+/// MinIO's distribution for `bucket/key` and streaming HighwayHash256 bitrot.
+/// The object gets the null version id, the current time, an MD5 etag and a
+/// data dir derived from its name, time and content.
+pub fn encode_object(
+    bucket: &str,
+    key: &str,
+    data: &[u8],
+    ec_m: usize,
+    ec_n: usize,
+    block_size: i64,
+) -> Result<EncodedObject> {
+    ensure!(block_size > 0, "invalid block size {}", block_size);
+    ensure!(
+        ec_m + ec_n <= u8::MAX as usize,
+        "too many shards: {}",
+        ec_m + ec_n
+    );
+    let rs = ReedSolomon::new(ec_m, ec_n).map_err(|e| anyhow!("create RS encoder: {:?}", e))?;
+    let total_shards = ec_m + ec_n;
+    let distribution = object_distribution(bucket, key, total_shards)?;
+    let mod_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0);
+
+    let shard_size = ceil_div(block_size, ec_m as i64);
+    let mut writers: Vec<_> = (0..total_shards)
+        .map(|_| ShardWriter::new(Vec::new(), shard_size))
+        .collect();
+    for block in data.chunks(block_size as usize) {
+        let len = block.len().div_ceil(ec_m);
+        let mut shards = vec![vec![0u8; len]; total_shards];
+        for (shard, chunk) in shards.iter_mut().zip(block.chunks(len)) {
+            shard[..chunk.len()].copy_from_slice(chunk);
+        }
+        rs.encode(&mut shards)
+            .map_err(|e| anyhow!("encode block: {:?}", e))?;
+        // Disk d stores shard distribution[d]
+        for (writer, &e) in writers.iter_mut().zip(&distribution) {
+            writer.write_block(&shards[e as usize - 1])?;
+        }
+    }
+    let shards = writers
+        .into_iter()
+        .map(|w| w.finish().map(|(file, _)| file))
+        .collect::<Result<Vec<_>>>()?;
+
+    let size = data.len() as i64;
+    let meta = ObjectMeta {
+        bucket: bucket.to_string(),
+        key: key.to_string(),
+        version_type: VersionType::Object,
+        data_dir: data_dir_for(bucket, key, mod_time, data),
+        data_blocks: ec_m,
+        parity_blocks: ec_n,
+        block_size,
+        distribution,
+        bitrot_algo: BitrotAlgorithm::HighwayHash256S,
+        parts: vec![PartMeta {
+            number: 1,
+            size,
+            actual_size: size,
+            ..Default::default()
+        }],
+        size,
+        mod_time,
+        etag: hex(&Md5::digest(data)),
+        content_type: "application/octet-stream".to_string(),
+        ..Default::default()
+    };
+    Ok(EncodedObject { meta, shards })
+}
+
+/// Random-looking (version 4 layout) UUID from the object's name, time and content
+fn data_dir_for(bucket: &str, key: &str, mod_time: i64, data: &[u8]) -> Uuid16 {
+    let mut h = Sha256::new();
+    h.update(bucket.as_bytes());
+    h.update([0]);
+    h.update(key.as_bytes());
+    h.update(mod_time.to_le_bytes());
+    h.update(data);
+    let mut id = [0u8; 16];
+    id.copy_from_slice(&h.finalize()[..16]);
+    id[6] = (id[6] & 0x0f) | 0x40;
+    id[8] = (id[8] & 0x3f) | 0x80;
+    Uuid16(id)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erasure::{decode_object, FsShardReader};
+    use crate::sink::FsShardSink;
+
+    #[test]
+    fn test_encode_decodes_back() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let obj = encode_object("bucket", "dir/obj", &data, 3, 2, 1024).unwrap();
+        assert!(crate::distribution::distribution_matches(&obj.meta));
+        assert_eq!(obj.meta.etag.len(), 32);
+        assert_eq!(obj.shards.len(), 5);
+        for shard in &obj.shards {
+            assert_eq!(
+                shard.len() as i64,
+                obj.meta.shard_file_size(data.len() as i64)
+            );
+        }
+
+        // Each disk's xl.meta describes the object with that disk's EcIndex
+        for disk in 0..5 {
+            let meta = xlmeta::parse(&obj.xlmeta(disk).unwrap()).unwrap();
+            assert_eq!(meta.erasure_index, obj.meta.distribution[disk] as usize);
+            assert_eq!(meta.distribution, obj.meta.distribution);
+            assert_eq!(
+                (meta.size, meta.mod_time),
+                (obj.meta.size, obj.meta.mod_time)
+            );
+            assert_eq!(meta.data_dir, obj.meta.data_dir);
+            assert_eq!(meta.etag, obj.meta.etag);
+        }
+
+        for skip in [&[][..], &[0, 1], &[3, 4]] {
+            assert!(decode_object(&obj, &obj.meta, skip).unwrap() == data);
+        }
+    }
+
+    #[test]
+    fn test_encode_written_layout_reads_from_disk() {
+        let data = vec![0x5a; 3000];
+        let obj = encode_object("b", "k", &data, 2, 2, 1000).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let disk_paths: Vec<String> = (0..4)
+            .map(|i| format!("{}/disk{}", dir.path().display(), i))
+            .collect();
+        obj.write_to(&FsShardSink {
+            disk_paths: disk_paths.clone(),
+        })
+        .unwrap();
+
+        let reader = FsShardReader { disk_paths };
+        let xl = reader.read_xlmeta(2, "b", "k").unwrap().unwrap();
+        let mut meta = xlmeta::parse(&xl).unwrap();
+        meta.bucket = "b".to_string();
+        meta.key = "k".to_string();
+        assert!(decode_object(&reader, &meta, &[1]).unwrap() == data);
+
+        // Empty objects have empty shard files
+        let empty = encode_object("b", "empty", &[], 2, 2, 1000).unwrap();
+        assert!(empty.shards.iter().all(Vec::is_empty));
+        assert_eq!(empty.meta.parts[0].size, 0);
+        assert!(encode_object("b", "k", &data, 0, 2, 1000).is_err());
+    }
+}
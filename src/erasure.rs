@@ -2,11 +2,17 @@
 //!
 //! Port of erasure/decoder.go. Reconstructs objects from erasure-coded shards.
 
+use std::collections::HashMap;
+use std::io::Write;
+
 use anyhow::{bail, Context, Result};
 use reed_solomon_erasure::galois_8::ReedSolomon;
 
+use crate::diagnostics::{DecodeReport, ShardDiagnostic, ShardIssue};
+use crate::layout::ShardLayout;
 use crate::shard;
-use crate::types::ObjectMeta;
+use crate::types::{ObjectMeta, PartMeta, Uuid16, VersionType};
+use crate::xlmeta;
 
 /// Trait for reading shard data. Abstracts over filesystem vs raw device reads.
 pub trait ShardReader {
@@ -20,6 +26,80 @@ pub trait ShardReader {
         data_dir: &str,
         part_number: i32,
     ) -> Result<Option<Vec<u8>>>;
+
+    /// Read the xl.meta file of an object on the given disk.
+    /// Returns Ok(None) if it is missing. The default reports every xl.meta as missing.
+    fn read_xlmeta(
+        &self,
+        _disk_index: usize,
+        _bucket: &str,
+        _key: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Read up to `len` bytes of a shard file starting at `offset`.
+    /// The result is shorter than `len` when the range runs past the end of
+    /// the file. Returns Ok(None) if the shard is missing.
+    ///
+    /// The default reads the whole shard with `read_shard`; backends that can
+    /// serve ranges directly (positional reads, mmap, HTTP ranges) should
+    /// override it.
+    #[allow(clippy::too_many_arguments)]
+    fn read_shard_range(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        let data = self.read_shard(disk_index, bucket, key, data_dir, part_number)?;
+        Ok(data.map(|data| {
+            let (start, len) = clamp_range(data.len() as u64, offset, len);
+            data[start as usize..start as usize + len].to_vec()
+        }))
+    }
+
+    /// Length of a shard file in bytes. Returns Ok(None) if the shard is missing.
+    ///
+    /// The default reads the whole shard with `read_shard`.
+    fn shard_len(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<u64>> {
+        let data = self.read_shard(disk_index, bucket, key, data_dir, part_number)?;
+        Ok(data.map(|data| data.len() as u64))
+    }
+}
+
+/// Clip `offset..offset + len` to a file of `size` bytes, returning `(start, len)`
+pub(crate) fn clamp_range(size: u64, offset: u64, len: usize) -> (u64, usize) {
+    let start = offset.min(size);
+    let len = (size - start).min(len as u64) as usize;
+    (start, len)
+}
+
+/// Path of `part.N` under a disk root, None if the disk is not configured
+pub(crate) fn part_path(
+    disk_paths: &[String],
+    disk_index: usize,
+    bucket: &str,
+    key: &str,
+    data_dir: &str,
+    part_number: i32,
+) -> Option<String> {
+    let disk = disk_paths.get(disk_index).filter(|p| !p.is_empty())?;
+    Some(format!(
+        "{}/{}/{}/{}/part.{}",
+        disk, bucket, key, data_dir, part_number
+    ))
 }
 
 /// Filesystem-based shard reader (reads from disk paths)
@@ -36,19 +116,381 @@ impl ShardReader for FsShardReader {
         data_dir: &str,
         part_number: i32,
     ) -> Result<Option<Vec<u8>>> {
-        if disk_index >= self.disk_paths.len() || self.disk_paths[disk_index].is_empty() {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
             return Ok(None);
-        }
-        let path = format!(
-            "{}/{}/{}/{}/part.{}",
-            self.disk_paths[disk_index], bucket, key, data_dir, part_number
-        );
+        };
         match std::fs::read(&path) {
             Ok(data) => Ok(Some(data)),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
+
+    fn read_shard_range(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let (start, len) = clamp_range(file.metadata()?.len(), offset, len);
+        let mut buf = vec![0u8; len];
+        shard::ReadAt::read_exact_at(&file, &mut buf, start)
+            .with_context(|| format!("read {} bytes at {} of {}", len, start, path))?;
+        Ok(Some(buf))
+    }
+
+    fn shard_len(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<u64>> {
+        // From the file's metadata, without reading it
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        match std::fs::metadata(&path) {
+            Ok(md) => Ok(Some(md.len())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read_xlmeta(&self, disk_index: usize, bucket: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        read_xlmeta_file(&self.disk_paths, disk_index, bucket, key)
+    }
+}
+
+/// Read `<disk>/<bucket>/<key>/xl.meta`, None if missing or the disk is not configured
+fn read_xlmeta_file(
+    disk_paths: &[String],
+    disk_index: usize,
+    bucket: &str,
+    key: &str,
+) -> Result<Option<Vec<u8>>> {
+    let Some(disk) = disk_paths.get(disk_index).filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+    let path = format!("{}/{}/{}/xl.meta", disk, bucket, key);
+    match std::fs::read(&path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Memory-mapped shard reader.
+///
+/// `map_shard` maps `part.N` instead of reading it, so block-level access via
+/// `shard::shard_block_slice` only faults in the pages of that block, and the
+/// page cache is shared between concurrent decodes. The most recently used
+/// maps are kept, up to `max_maps`; a cached map is only reused while the
+/// file's size and modification time are unchanged, so files replaced by heal
+/// are mapped afresh. Shard files must be replaced, never rewritten in place,
+/// while mapped: truncating a mapped file makes reads of the lost pages fault.
+#[cfg(feature = "mmap")]
+pub struct MmapShardReader {
+    disk_paths: Vec<String>,
+    max_maps: usize,
+    maps: std::sync::Mutex<MmapCache>,
+}
+
+/// Maps by path, with the file identity they were made from
+#[cfg(feature = "mmap")]
+#[derive(Default)]
+struct MmapCache {
+    entries: HashMap<String, CachedMap>,
+    // Incremented on every lookup; entries with the lowest `used` go first
+    clock: u64,
+}
+
+#[cfg(feature = "mmap")]
+struct CachedMap {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+    used: u64,
+    map: std::sync::Arc<memmap2::Mmap>,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapShardReader {
+    /// Maps kept by `new`
+    pub const DEFAULT_MAX_MAPS: usize = 256;
+
+    pub fn new(disk_paths: Vec<String>) -> Self {
+        Self::with_max_maps(disk_paths, Self::DEFAULT_MAX_MAPS)
+    }
+
+    /// Reader keeping at most `max_maps` maps (0 disables caching)
+    pub fn with_max_maps(disk_paths: Vec<String>, max_maps: usize) -> Self {
+        MmapShardReader {
+            disk_paths,
+            max_maps,
+            maps: Default::default(),
+        }
+    }
+
+    pub fn disk_paths(&self) -> &[String] {
+        &self.disk_paths
+    }
+
+    /// Map a shard file read-only, or return its cached map if the file is
+    /// unchanged. Returns Ok(None) if the shard is missing.
+    pub fn map_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<std::sync::Arc<memmap2::Mmap>>> {
+        let Some(path) = part_path(
+            &self.disk_paths,
+            disk_index,
+            bucket,
+            key,
+            data_dir,
+            part_number,
+        ) else {
+            return Ok(None);
+        };
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let stat = file.metadata().with_context(|| format!("stat {}", path))?;
+        let (len, modified) = (stat.len(), stat.modified().ok());
+
+        let mut cache = self.maps.lock().unwrap_or_else(|e| e.into_inner());
+        cache.clock += 1;
+        let clock = cache.clock;
+        if let Some(cached) = cache.entries.get_mut(&path) {
+            if cached.len == len && cached.modified == modified {
+                cached.used = clock;
+                return Ok(Some(cached.map.clone()));
+            }
+        }
+
+        // SAFETY: the map is read-only, and shard files are only ever replaced
+        // by renaming a new file over them (as `FsShardSink` does), which
+        // leaves the mapped inode intact. Truncating or writing a shard file in
+        // place while it is mapped is unsupported; see the type docs.
+        let map = unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("mmap {}", path))?;
+        let map = std::sync::Arc::new(map);
+        if self.max_maps == 0 {
+            return Ok(Some(map));
+        }
+        if !cache.entries.contains_key(&path) && cache.entries.len() >= self.max_maps {
+            let oldest = cache
+                .entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.used)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                cache.entries.remove(&oldest);
+            }
+        }
+        let cached = CachedMap {
+            len,
+            modified,
+            used: clock,
+            map: map.clone(),
+        };
+        cache.entries.insert(path, cached);
+        Ok(Some(map))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl ShardReader for MmapShardReader {
+    /// Copies the whole file, as the trait returns owned data. Decoding only
+    /// reads whole shards for whole-file bitrot; use `map_shard` to borrow.
+    fn read_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<Vec<u8>>> {
+        let map = self.map_shard(disk_index, bucket, key, data_dir, part_number)?;
+        Ok(map.map(|m| m.to_vec()))
+    }
+
+    fn read_shard_range(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        let map = self.map_shard(disk_index, bucket, key, data_dir, part_number)?;
+        Ok(map.map(|m| {
+            let (start, len) = clamp_range(m.len() as u64, offset, len);
+            m[start as usize..start as usize + len].to_vec()
+        }))
+    }
+
+    fn shard_len(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<u64>> {
+        let map = self.map_shard(disk_index, bucket, key, data_dir, part_number)?;
+        Ok(map.map(|m| m.len() as u64))
+    }
+
+    fn read_xlmeta(&self, disk_index: usize, bucket: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        read_xlmeta_file(&self.disk_paths, disk_index, bucket, key)
+    }
+}
+
+/// Result of decoding a specific object version
+#[derive(Debug)]
+pub enum VersionedObject {
+    /// The version holds object data
+    Object { meta: ObjectMeta, data: Vec<u8> },
+    /// The version is a delete marker
+    DeleteMarker(ObjectMeta),
+    /// No disk has this version
+    NotFound,
+}
+
+/// Decode one specific version of an object.
+///
+/// `version_id` is an S3 version id; "null" selects the null version of
+/// unversioned or versioning-suspended buckets. xl.meta is read from disks
+/// `0..disk_count` (minus `skip_disks`) and the first copy that lists the
+/// version is used. Versions with inline data are decoded from the shards
+/// stored in each disk's xl.meta. Errors if no copy lists the version and
+/// some xl.meta could not be read or parsed.
+pub fn decode_object_version(
+    reader: &dyn ShardReader,
+    disk_count: usize,
+    bucket: &str,
+    key: &str,
+    version_id: &str,
+    skip_disks: &[usize],
+) -> Result<VersionedObject> {
+    let version_id = Uuid16::from_version_id(version_id)?;
+
+    let mut copies = Vec::new();
+    let mut failure = None;
+    for disk_idx in (0..disk_count).filter(|d| !skip_disks.contains(d)) {
+        let parsed = reader
+            .read_xlmeta(disk_idx, bucket, key)
+            .and_then(|data| data.map(|data| xlmeta::parse_full(&data)).transpose());
+        match parsed {
+            Ok(Some(xl)) => copies.push((disk_idx, xl)),
+            Ok(None) => {}
+            Err(e) => {
+                failure = Some(e.context(format!(
+                    "xl.meta of {}/{} on disk {}",
+                    bucket, key, disk_idx
+                )))
+            }
+        }
+    }
+
+    let Some(version) = copies
+        .iter()
+        .find_map(|(_, xl)| xl.find_version(&version_id))
+    else {
+        return match failure {
+            Some(e) => Err(e),
+            None => Ok(VersionedObject::NotFound),
+        };
+    };
+    let inline = version.header.has_inline_data();
+    let mut meta = version.meta.clone();
+    meta.bucket = bucket.to_string();
+    meta.key = key.to_string();
+
+    match meta.version_type {
+        VersionType::Object if inline => {
+            let id = version_id.to_version_id_string();
+            let mut shards = vec![None; disk_count];
+            for (disk_idx, xl) in &copies {
+                shards[*disk_idx] = xl
+                    .inline_data
+                    .iter()
+                    .find(|(k, _)| *k == id)
+                    .map(|(_, data)| data.clone());
+            }
+            let data = decode_object(&InlineShardReader { shards }, &meta, skip_disks)?;
+            Ok(VersionedObject::Object { meta, data })
+        }
+        VersionType::Object | VersionType::Legacy => {
+            let data = decode_object(reader, &meta, skip_disks)?;
+            Ok(VersionedObject::Object { meta, data })
+        }
+        VersionType::DeleteMarker => Ok(VersionedObject::DeleteMarker(meta)),
+        other => bail!("unsupported version type {:?}", other),
+    }
+}
+
+/// Shards of an inline-data version, one per disk, taken from its xl.meta copies
+struct InlineShardReader {
+    shards: Vec<Option<Vec<u8>>>,
+}
+
+impl ShardReader for InlineShardReader {
+    fn read_shard(
+        &self,
+        disk_index: usize,
+        _bucket: &str,
+        _key: &str,
+        _data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<Vec<u8>>> {
+        // Inline objects always have a single part
+        if part_number != 1 {
+            return Ok(None);
+        }
+        Ok(self.shards.get(disk_index).cloned().flatten())
+    }
 }
 
 /// Decode a complete object from erasure-coded shards.
@@ -59,12 +501,41 @@ pub fn decode_object(
     reader: &dyn ShardReader,
     meta: &ObjectMeta,
     skip_disks: &[usize],
+) -> Result<Vec<u8>> {
+    decode_object_inner(reader, meta, skip_disks, None)
+}
+
+/// Decode an object like `decode_object`, adding every shard block that was
+/// skipped, missing, unreadable or failed bitrot to `report`, per disk and
+/// block. The report is filled in even when decoding fails.
+pub fn decode_object_with_report(
+    reader: &dyn ShardReader,
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+    report: &mut DecodeReport,
+) -> Result<Vec<u8>> {
+    decode_object_inner(reader, meta, skip_disks, Some(report))
+}
+
+fn decode_object_inner(
+    reader: &dyn ShardReader,
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+    mut report: Option<&mut DecodeReport>,
 ) -> Result<Vec<u8>> {
     let mut result = Vec::with_capacity(meta.size as usize);
+    let checksums = ShardChecksums::read(reader, meta, skip_disks);
 
     for part in &meta.parts {
-        let part_data = decode_part(reader, meta, part.number, part.size, skip_disks)?;
-        result.extend_from_slice(&part_data);
+        decode_part(
+            reader,
+            meta,
+            part,
+            skip_disks,
+            &checksums,
+            &mut result,
+            report.as_deref_mut(),
+        )?;
     }
 
     // Trim to actual object size
@@ -75,63 +546,483 @@ pub fn decode_object(
     Ok(result)
 }
 
-/// Decode a single part of an object
-fn decode_part(
+/// Decode `len` bytes of an object starting at `offset`, clipped to the object size.
+///
+/// Only the blocks overlapping the range are decoded, and with streaming
+/// bitrot only those blocks are fetched from each shard. Ranges may span
+/// several parts of a multipart object.
+pub fn decode_object_range(
     reader: &dyn ShardReader,
     meta: &ObjectMeta,
-    part_number: i32,
-    part_size: i64,
+    offset: i64,
+    len: i64,
     skip_disks: &[usize],
 ) -> Result<Vec<u8>> {
-    let data_dir = meta.data_dir_string();
+    if offset < 0 || len < 0 {
+        bail!("invalid range: offset {}, length {}", offset, len);
+    }
+    if offset > meta.size {
+        bail!("range start {} beyond object size {}", offset, meta.size);
+    }
+    let end = offset + std::cmp::min(len, meta.size - offset);
+    let mut result = Vec::with_capacity((end - offset) as usize);
+    decode_blocks(reader, meta, offset, end, skip_disks, |data| {
+        result.extend_from_slice(data);
+        Ok(())
+    })?;
+    Ok(result)
+}
+
+/// Decode a whole object block by block into `out`, returning the bytes written.
+///
+/// Unlike `decode_object`, memory use is bounded by one block of every shard
+/// (whole shard files for whole-file bitrot, which can only be verified whole).
+pub fn decode_object_to_writer<W: Write + ?Sized>(
+    reader: &dyn ShardReader,
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+    out: &mut W,
+) -> Result<u64> {
+    let mut written = 0u64;
+    decode_blocks(reader, meta, 0, meta.size, skip_disks, |data| {
+        out.write_all(data).context("write decoded block")?;
+        written += data.len() as u64;
+        Ok(())
+    })?;
+    out.flush().context("flush decoded object")?;
+    Ok(written)
+}
+
+/// A block to decode and the bytes of it to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlockSpan {
+    /// Index into `meta.parts`
+    pub part: usize,
+    pub block: usize,
+    /// Kept bytes of the decoded block
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The blocks covering object bytes `offset..end`, in order
+pub(crate) fn block_spans(meta: &ObjectMeta, offset: i64, end: i64) -> Vec<BlockSpan> {
+    let mut spans = Vec::new();
+    if end <= offset {
+        return spans;
+    }
+    let mut part_start = 0i64;
+    for (part_idx, part) in meta.parts.iter().enumerate() {
+        if part_start >= end {
+            break;
+        }
+        let part_end = part_start + part.size;
+        if part_end > offset {
+            // Range within this part
+            let from = std::cmp::max(offset, part_start) - part_start;
+            let to = std::cmp::min(end, part_end) - part_start;
+
+            let layout = ShardLayout::for_part_size(meta, part.size);
+            let first = layout.block_for_offset(from);
+            let last = layout.block_for_offset(to - 1);
+            for block in (first..=last).filter_map(|i| layout.block(i)) {
+                // Trim the block to the requested range
+                let start = std::cmp::max(from, block.part_offset) - block.part_offset;
+                let stop = std::cmp::min(to, block.part_offset + block.part_size);
+                spans.push(BlockSpan {
+                    part: part_idx,
+                    block: block.index,
+                    start: start as usize,
+                    end: (stop - block.part_offset) as usize,
+                });
+            }
+        }
+        part_start = part_end;
+    }
+    spans
+}
+
+/// Decode the blocks covering object bytes `offset..end` in order, passing
+/// each block trimmed to the range to `emit`.
+fn decode_blocks(
+    reader: &dyn ShardReader,
+    meta: &ObjectMeta,
+    offset: i64,
+    end: i64,
+    skip_disks: &[usize],
+    mut emit: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let shard_size = meta.shard_size();
+    let checksums = ShardChecksums::read(reader, meta, skip_disks);
+    let mut block_data = Vec::new();
+    let mut current: Option<(usize, PartShards)> = None;
+    for span in block_spans(meta, offset, end) {
+        let part = &meta.parts[span.part];
+        let shards = match &mut current {
+            Some((i, shards)) if *i == span.part => shards,
+            slot => {
+                let layout = ShardLayout::for_part_size(meta, part.size);
+                let shards = PartShards::new(
+                    reader,
+                    meta,
+                    part.number,
+                    skip_disks,
+                    Some(layout),
+                    &checksums,
+                );
+                &mut slot.insert((span.part, shards)).1
+            }
+        };
+
+        block_data.clear();
+        decode_block(shards, span.block, shard_size, &mut block_data, None)
+            .with_context(|| format!("decode part {} block {}", part.number, span.block))?;
+        let data = block_data
+            .get(span.start..span.end)
+            .context("decoded block shorter than expected")?;
+        emit(data)?;
+    }
+    Ok(())
+}
+
+/// Decode a single part of an object, appending it to `out`
+fn decode_part(
+    reader: &dyn ShardReader,
+    meta: &ObjectMeta,
+    part: &PartMeta,
+    skip_disks: &[usize],
+    checksums: &ShardChecksums,
+    out: &mut Vec<u8>,
+    mut report: Option<&mut DecodeReport>,
+) -> Result<()> {
     let shard_size = meta.shard_size();
 
     // Calculate number of blocks in this part
-    let num_blocks = if part_size == 0 {
+    let num_blocks = if part.size == 0 {
         1
     } else {
-        ((part_size + meta.block_size - 1) / meta.block_size) as usize
+        ((part.size + meta.block_size - 1) / meta.block_size) as usize
     };
 
-    let mut result = Vec::with_capacity(part_size as usize);
+    let mut shards = PartShards::new(reader, meta, part.number, skip_disks, None, checksums);
+
+    let part_start = out.len();
+    out.reserve(part.size as usize);
 
     for block in 0..num_blocks {
-        let block_data = decode_block(
-            reader,
+        decode_block(&mut shards, block, shard_size, out, report.as_deref_mut())
+            .with_context(|| format!("decode block {}", block))?;
+
+        // Trim shard padding; blocks hold up to data_blocks * shard_size bytes,
+        // which exceeds block_size when it does not divide evenly
+        let block_end = std::cmp::min(part.size, (block as i64 + 1) * meta.block_size);
+        if (out.len() - part_start) as i64 > block_end {
+            out.truncate(part_start + block_end as usize);
+        }
+    }
+
+    Ok(())
+}
+
+/// What to read for one shard of a part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShardFetch {
+    /// The whole shard file
+    Whole,
+    /// `len` bytes at `offset`: the hash and data of a single block
+    Range { offset: u64, len: usize },
+}
+
+/// Shards still to fetch for a block, as `(shard_idx, disk_idx, fetch)`
+pub(crate) type PendingFetches = Vec<(usize, usize, ShardFetch)>;
+
+/// Reader-independent state of decoding one part: which shard lives on
+/// which disk, what to fetch for a block, and the shards fetched so far.
+///
+/// With a layout and streaming bitrot, only the block being decoded is
+/// fetched from each shard; otherwise every shard file is fetched at most
+/// once and shared by all of the part's blocks.
+pub(crate) struct PartState<'a> {
+    meta: &'a ObjectMeta,
+    data_dir: String,
+    part_number: i32,
+    skip_disks: &'a [usize],
+    // shard_idx (0-based) -> disk_idx (0-based)
+    shard_to_disk: Vec<Option<usize>>,
+    // Whole-file bitrot checksums, indexed by shard (empty for streaming bitrot)
+    checksums: Vec<Option<Vec<u8>>>,
+    // Set when reading block by block
+    ranged: Option<ShardLayout>,
+    // Block held in `files` when reading block by block
+    loaded_block: usize,
+    // None = not read yet
+    files: Vec<Option<Result<Vec<u8>, ShardIssue>>>,
+}
+
+impl<'a> PartState<'a> {
+    /// State for a part; `checksums` are only used for whole-file bitrot
+    pub(crate) fn new(
+        meta: &'a ObjectMeta,
+        part_number: i32,
+        skip_disks: &'a [usize],
+        ranged: Option<ShardLayout>,
+        checksums: Vec<Option<Vec<u8>>>,
+    ) -> Self {
+        PartState {
             meta,
-            &data_dir,
+            data_dir: meta.data_dir_string(),
             part_number,
-            block,
-            shard_size,
             skip_disks,
-        )
-        .with_context(|| format!("decode block {}", block))?;
-        result.extend_from_slice(&block_data);
+            shard_to_disk: shard_disks(meta),
+            checksums,
+            // Whole-file checksums can only be verified on whole files
+            ranged: ranged.filter(|_| meta.bitrot_algo.is_streaming()),
+            loaded_block: 0,
+            files: vec![None; meta.total_shards()],
+        }
     }
 
-    // Trim to part size
-    if result.len() as i64 > part_size {
-        result.truncate(part_size as usize);
+    pub(crate) fn meta(&self) -> &'a ObjectMeta {
+        self.meta
     }
 
-    Ok(result)
+    pub(crate) fn data_dir(&self) -> &str {
+        &self.data_dir
+    }
+
+    pub(crate) fn part_number(&self) -> i32 {
+        self.part_number
+    }
+
+    /// Shards of `range` still to fetch for block `block_index`. Shards on
+    /// no disk or on a skipped disk are recorded as unusable instead.
+    pub(crate) fn pending(
+        &mut self,
+        range: std::ops::Range<usize>,
+        block_index: usize,
+    ) -> PendingFetches {
+        if self.ranged.is_some() && self.loaded_block != block_index {
+            self.files.iter_mut().for_each(|f| *f = None);
+            self.loaded_block = block_index;
+        }
+        let fetch = match &self.ranged {
+            Some(layout) => layout.block(block_index).map(|block| {
+                let offset = block.hash_offset.unwrap_or(block.data_offset);
+                let len = (block.data_offset - offset + block.data_size) as usize;
+                ShardFetch::Range { offset, len }
+            }),
+            None => Some(ShardFetch::Whole),
+        };
+
+        let mut pending = Vec::new();
+        for shard_idx in range {
+            if self.files[shard_idx].is_some() {
+                continue;
+            }
+            match (self.shard_to_disk[shard_idx], fetch) {
+                (Some(disk), _) if self.skip_disks.contains(&disk) => {
+                    self.files[shard_idx] = Some(Err(ShardIssue::Skipped));
+                }
+                (Some(disk), Some(fetch)) => pending.push((shard_idx, disk, fetch)),
+                _ => self.files[shard_idx] = Some(Err(ShardIssue::Missing)),
+            }
+        }
+        pending
+    }
+
+    /// Record the result of fetching a shard, verifying it if it has a
+    /// whole-file checksum
+    pub(crate) fn store(&mut self, shard_idx: usize, data: Result<Option<Vec<u8>>>) {
+        let slot = match data {
+            Ok(Some(data)) => match self.checksums.get(shard_idx) {
+                Some(Some(expected)) if self.ranged.is_none() => {
+                    match shard::verify_shard_file(&data, self.meta.bitrot_algo, expected) {
+                        Ok(()) => Ok(data),
+                        Err(e) => Err(ShardIssue::BitrotFailed(format!("{:#}", e))),
+                    }
+                }
+                _ => Ok(data),
+            },
+            Ok(None) => Err(ShardIssue::Missing),
+            Err(e) => Err(ShardIssue::Unreadable(format!("{:#}", e))),
+        };
+        self.files[shard_idx] = Some(slot);
+    }
+
+    /// View of one block of a fetched shard, None if unusable
+    pub(crate) fn block(
+        &self,
+        shard_idx: usize,
+        block_index: usize,
+        shard_size: i64,
+    ) -> Option<&[u8]> {
+        self.checked_block(shard_idx, block_index, shard_size).ok()
+    }
+
+    /// View of one block of a fetched shard, or why it cannot be used
+    pub(crate) fn checked_block(
+        &self,
+        shard_idx: usize,
+        block_index: usize,
+        shard_size: i64,
+    ) -> Result<&[u8], ShardIssue> {
+        let file = match &self.files[shard_idx] {
+            Some(Ok(file)) => file,
+            Some(Err(issue)) => return Err(issue.clone()),
+            None => return Err(ShardIssue::Missing),
+        };
+        // A ranged read holds just this block, laid out like a one-block shard
+        let index = if self.ranged.is_some() {
+            0
+        } else {
+            block_index
+        };
+        match shard::shard_block_slice_with(file, index, shard_size, self.meta.bitrot_algo, true) {
+            Ok(Some(block)) => Ok(block),
+            Ok(None) => Err(ShardIssue::BitrotFailed(format!(
+                "block {} missing from short shard file",
+                block_index
+            ))),
+            Err(e) => Err(ShardIssue::BitrotFailed(format!("{:#}", e))),
+        }
+    }
+
+    /// Decode block `block_index` from the fetched shards, appending it to `out`.
+    ///
+    /// When data shards are missing and parity has not been fetched yet,
+    /// returns the parity fetches needed instead and leaves `out` untouched.
+    /// Unusable shard blocks are added to `report` once the block is done.
+    pub(crate) fn decode(
+        &mut self,
+        block_index: usize,
+        shard_size: i64,
+        out: &mut Vec<u8>,
+        report: Option<&mut DecodeReport>,
+    ) -> Result<Option<PendingFetches>> {
+        let data_blocks = self.meta.data_blocks;
+        let total_shards = self.meta.total_shards();
+
+        // If all data shards present, fast path — copy each block once
+        let data: Vec<Option<&[u8]>> = (0..data_blocks)
+            .map(|i| self.block(i, block_index, shard_size))
+            .collect();
+        if data.iter().all(Option::is_some) {
+            for block in data.into_iter().flatten() {
+                out.extend_from_slice(block);
+            }
+            return Ok(None);
+        }
+
+        // Need reconstruction — parity shards must be fetched first
+        let pending = self.pending(data_blocks..total_shards, block_index);
+        if !pending.is_empty() {
+            return Ok(Some(pending));
+        }
+        let checked: Vec<Result<&[u8], ShardIssue>> = (0..total_shards)
+            .map(|i| self.checked_block(i, block_index, shard_size))
+            .collect();
+        let blocks: Vec<Option<&[u8]>> = checked.iter().map(|b| b.as_deref().ok()).collect();
+        let result = reconstruct_block(self.meta, &blocks, out);
+
+        if let Some(report) = report {
+            for (shard_idx, block) in checked.iter().enumerate() {
+                if let Err(issue) = block {
+                    report.shards.push(ShardDiagnostic {
+                        disk_index: self.shard_to_disk[shard_idx],
+                        erasure_index: shard_idx + 1,
+                        part_number: self.part_number,
+                        block: block_index,
+                        issue: issue.clone(),
+                        reconstructed: result.is_ok() && shard_idx < data_blocks,
+                    });
+                }
+            }
+        }
+        result?;
+        Ok(None)
+    }
 }
 
-/// Decode a single block of a part
-fn decode_block(
-    reader: &dyn ShardReader,
-    meta: &ObjectMeta,
-    data_dir: &str,
-    part_number: i32,
-    block_index: usize,
-    shard_size: i64,
-    skip_disks: &[usize],
-) -> Result<Vec<u8>> {
-    let data_blocks = meta.data_blocks;
-    let parity_blocks = meta.parity_blocks;
-    let total_shards = data_blocks + parity_blocks;
+/// Shard files of one part read through a `ShardReader`, each at most once
+/// and shared by all of the part's blocks. Parity shards are only read once
+/// a block needs them.
+pub(crate) struct PartShards<'a> {
+    reader: &'a dyn ShardReader,
+    state: PartState<'a>,
+}
+
+impl<'a> PartShards<'a> {
+    pub(crate) fn new(
+        reader: &'a dyn ShardReader,
+        meta: &'a ObjectMeta,
+        part_number: i32,
+        skip_disks: &'a [usize],
+        ranged: Option<ShardLayout>,
+        checksums: &ShardChecksums,
+    ) -> Self {
+        let checksums = checksums.part(part_number);
+        PartShards {
+            reader,
+            state: PartState::new(meta, part_number, skip_disks, ranged, checksums),
+        }
+    }
+
+    /// Read shards `range` of block `block_index` that have not been read yet
+    pub(crate) fn load(&mut self, range: std::ops::Range<usize>, block_index: usize) {
+        let pending = self.state.pending(range, block_index);
+        self.fetch_all(pending);
+    }
+
+    fn fetch_all(&mut self, pending: PendingFetches) {
+        for (shard_idx, disk_idx, fetch) in pending {
+            let data = self.fetch(disk_idx, fetch);
+            self.state.store(shard_idx, data);
+        }
+    }
+
+    /// Read one shard file, or only one block of it
+    fn fetch(&self, disk_idx: usize, fetch: ShardFetch) -> Result<Option<Vec<u8>>> {
+        let meta = self.state.meta();
+        let (bucket, key) = (&meta.bucket, &meta.key);
+        let (data_dir, part) = (self.state.data_dir(), self.state.part_number());
+        match fetch {
+            ShardFetch::Whole => self
+                .reader
+                .read_shard(disk_idx, bucket, key, data_dir, part),
+            ShardFetch::Range { offset, len } => self
+                .reader
+                .read_shard_range(disk_idx, bucket, key, data_dir, part, offset, len),
+        }
+    }
+
+    /// View of one block of a loaded shard, None if missing or failing bitrot
+    pub(crate) fn block(
+        &self,
+        shard_idx: usize,
+        block_index: usize,
+        shard_size: i64,
+    ) -> Option<&[u8]> {
+        self.state.block(shard_idx, block_index, shard_size)
+    }
+
+    /// View of one block of a loaded shard, or why it cannot be used
+    pub(crate) fn checked_block(
+        &self,
+        shard_idx: usize,
+        block_index: usize,
+        shard_size: i64,
+    ) -> Result<&[u8], ShardIssue> {
+        self.state.checked_block(shard_idx, block_index, shard_size)
+    }
+
+    /// Disk holding shard `shard_idx`
+    pub(crate) fn disk_of(&self, shard_idx: usize) -> Option<usize> {
+        self.state.shard_to_disk[shard_idx]
+    }
+}
 
-    // Build reverse mapping: shard_idx (0-based) -> disk_idx (0-based)
+/// Reverse of the distribution: shard_idx (0-based) -> disk_idx (0-based)
+pub(crate) fn shard_disks(meta: &ObjectMeta) -> Vec<Option<usize>> {
+    let total_shards = meta.total_shards();
     // Distribution[disk_idx] = erasure_index (1-based shard number)
     let mut shard_to_disk: Vec<Option<usize>> = vec![None; total_shards];
     for (disk_idx, &erasure_idx) in meta.distribution.iter().enumerate() {
@@ -140,43 +1031,141 @@ fn decode_block(
             shard_to_disk[shard_idx] = Some(disk_idx);
         }
     }
+    shard_to_disk
+}
+
+/// Whole-file bitrot checksums of every part of an object, gathered once per
+/// object. Empty for streaming bitrot, whose hashes live in the shards.
+///
+/// Each disk's xl.meta only holds the checksum of its own shard, so the
+/// copies on the other disks are consulted for the rest. Shards whose
+/// checksum cannot be found are used unverified.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShardChecksums {
+    // part number -> checksum per shard (0-based)
+    parts: HashMap<i32, Vec<Option<Vec<u8>>>>,
+}
 
-    // Helper to read one shard block
-    let read_one_shard = |shard_idx: usize| -> Option<Vec<u8>> {
-        let disk_idx = shard_to_disk[shard_idx]?;
-        if skip_disks.contains(&disk_idx) {
-            return None;
+impl ShardChecksums {
+    /// Checksums known from `meta` alone
+    pub(crate) fn own(meta: &ObjectMeta) -> Self {
+        let mut checksums = ShardChecksums::default();
+        if meta.bitrot_algo.is_streaming() {
+            return checksums;
         }
-        let shard_data = reader
-            .read_shard(disk_idx, &meta.bucket, &meta.key, data_dir, part_number)
-            .ok()??;
-        shard::read_shard_block(&shard_data, block_index, shard_size, true).ok()?
-    };
+        let total_shards = meta.total_shards();
+        for part in &meta.parts {
+            let mut shards = vec![None; total_shards];
+            if (1..=total_shards).contains(&meta.erasure_index) {
+                shards[meta.erasure_index - 1] = part_checksum(meta, part.number);
+            }
+            checksums.parts.insert(part.number, shards);
+        }
+        checksums
+    }
 
-    // Step 1: Read only data shards (first data_blocks)
-    let mut shards: Vec<Option<Vec<u8>>> = Vec::with_capacity(total_shards);
-    for shard_idx in 0..data_blocks {
-        shards.push(read_one_shard(shard_idx));
+    /// Read the checksums `own` lacks from the other disks' xl.meta copies
+    pub(crate) fn read(reader: &dyn ShardReader, meta: &ObjectMeta, skip_disks: &[usize]) -> Self {
+        let mut checksums = Self::own(meta);
+        for disk_idx in checksums.missing_disks(meta, skip_disks) {
+            if let Ok(Some(xl)) = reader.read_xlmeta(disk_idx, &meta.bucket, &meta.key) {
+                checksums.add_copy(meta, disk_idx, &xl);
+            }
+        }
+        checksums
     }
 
-    // Count successful data shards
-    let data_success = shards.iter().filter(|s| s.is_some()).count();
+    /// Disks whose xl.meta holds a checksum still missing
+    pub(crate) fn missing_disks(&self, meta: &ObjectMeta, skip_disks: &[usize]) -> Vec<usize> {
+        (0..meta.distribution.len())
+            .filter(|disk_idx| !skip_disks.contains(disk_idx))
+            .filter(|&disk_idx| {
+                let Some(shard_idx) = disk_shard(meta, disk_idx) else {
+                    return false;
+                };
+                self.parts
+                    .values()
+                    .any(|shards| shards[shard_idx].is_none())
+            })
+            .collect()
+    }
 
-    // Step 2: If all data shards present, fast path — just concatenate
-    if data_success == data_blocks {
-        let mut block_data = Vec::new();
-        for s in &shards {
-            block_data.extend_from_slice(s.as_ref().unwrap());
+    /// Take the checksums of disk `disk_idx`'s shard from its xl.meta copy,
+    /// if that copy has the same version and belongs to that shard
+    pub(crate) fn add_copy(&mut self, meta: &ObjectMeta, disk_idx: usize, xl: &[u8]) {
+        let Some(shard_idx) = disk_shard(meta, disk_idx) else {
+            return;
+        };
+        let Ok(xl) = xlmeta::parse_full(xl) else {
+            return;
+        };
+        let Some(v) = xl.find_version(&meta.version_id) else {
+            return;
+        };
+        if v.meta.erasure_index != shard_idx + 1 {
+            return;
+        }
+        for (&number, shards) in &mut self.parts {
+            if shards[shard_idx].is_none() {
+                shards[shard_idx] = part_checksum(&v.meta, number);
+            }
         }
-        return Ok(block_data);
     }
 
-    // Step 3: Need reconstruction — read parity shards
-    for shard_idx in data_blocks..total_shards {
-        shards.push(read_one_shard(shard_idx));
+    /// Checksums of part `part_number`, indexed by shard (0-based)
+    pub(crate) fn part(&self, part_number: i32) -> Vec<Option<Vec<u8>>> {
+        self.parts.get(&part_number).cloned().unwrap_or_default()
+    }
+}
+
+/// Shard (0-based) that the distribution places on disk `disk_idx`
+fn disk_shard(meta: &ObjectMeta, disk_idx: usize) -> Option<usize> {
+    let shard_idx = (*meta.distribution.get(disk_idx)? as usize).checked_sub(1)?;
+    (shard_idx < meta.total_shards()).then_some(shard_idx)
+}
+
+fn part_checksum(meta: &ObjectMeta, part_number: i32) -> Option<Vec<u8>> {
+    meta.parts
+        .iter()
+        .find(|p| p.number == part_number && !p.checksum.is_empty())
+        .map(|p| p.checksum.clone())
+}
+
+/// Decode a single block of a part, appending it to `out`.
+///
+/// When all data shards are readable their blocks are copied straight from
+/// the shard buffers into `out`; parity is only read and Reed-Solomon
+/// buffers only built when reconstruction is needed.
+pub(crate) fn decode_block(
+    shards: &mut PartShards<'_>,
+    block_index: usize,
+    shard_size: i64,
+    out: &mut Vec<u8>,
+    mut report: Option<&mut DecodeReport>,
+) -> Result<()> {
+    // Read only data shards (first data_blocks)
+    shards.load(0..shards.state.meta().data_blocks, block_index);
+    while let Some(pending) =
+        shards
+            .state
+            .decode(block_index, shard_size, out, report.as_deref_mut())?
+    {
+        shards.fetch_all(pending);
     }
+    Ok(())
+}
+
+/// Reed-Solomon reconstruct one block from its shard blocks (data then
+/// parity, None where unavailable), appending the data shards to `out`.
+pub(crate) fn reconstruct_block(
+    meta: &ObjectMeta,
+    blocks: &[Option<&[u8]>],
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let data_blocks = meta.data_blocks;
+    let parity_blocks = meta.parity_blocks;
 
-    let available = shards.iter().filter(|s| s.is_some()).count();
+    let available = blocks.iter().filter(|b| b.is_some()).count();
     if available < data_blocks {
         bail!(
             "insufficient shards: have {}, need {}",
@@ -190,17 +1179,19 @@ fn decode_block(
         .map_err(|e| anyhow::anyhow!("create RS encoder: {:?}", e))?;
 
     // Normalize shard sizes — all must be the same length for RS
-    let max_size = shards
+    let max_size = blocks
         .iter()
-        .filter_map(|s| s.as_ref().map(|v| v.len()))
+        .filter_map(|b| b.map(<[u8]>::len))
         .max()
         .unwrap_or(0);
 
-    // Pad shorter shards to max_size
-    let mut rs_shards: Vec<Option<Vec<u8>>> = shards
-        .into_iter()
-        .map(|s| {
-            s.map(|mut v| {
+    // Copy blocks into RS buffers, padding shorter ones to max_size
+    let mut rs_shards: Vec<Option<Vec<u8>>> = blocks
+        .iter()
+        .map(|b| {
+            b.map(|b| {
+                let mut v = Vec::with_capacity(max_size);
+                v.extend_from_slice(b);
                 v.resize(max_size, 0);
                 v
             })
@@ -212,22 +1203,24 @@ fn decode_block(
         .map_err(|e| anyhow::anyhow!("reconstruction failed: {:?}", e))?;
 
     // Concatenate data shards
-    let mut block_data = Vec::with_capacity(data_blocks * max_size);
     for (i, shard) in rs_shards.iter().enumerate().take(data_blocks) {
         if let Some(ref shard_data) = shard {
-            block_data.extend_from_slice(shard_data);
+            out.extend_from_slice(shard_data);
         } else {
             bail!("data shard {} still missing after reconstruction", i);
         }
     }
 
-    Ok(block_data)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::PartMeta;
+    use std::sync::atomic::Ordering;
+
+    use crate::testutil::{corpus_meta, corpus_reader, encode_shard_files, multipart_object};
+    use crate::types::BitrotAlgorithm;
 
     /// Mock ShardReader for testing
     struct MockShardReader {
@@ -263,6 +1256,221 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_decode_cicd_corpus_with_missing_disks() {
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+
+        let full = decode_object(&reader, &meta, &[]).unwrap();
+        assert_eq!(full.len(), 644520);
+
+        // Any two disks may be lost with EC 3+2
+        for skip in [[0, 1], [1, 2], [3, 4], [0, 4]] {
+            let data = decode_object(&reader, &meta, &skip).unwrap();
+            assert!(data == full, "mismatch skipping disks {:?}", skip);
+        }
+        assert!(decode_object(&reader, &meta, &[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_shard_range_reads_match_full_reads() {
+        let fs = corpus_reader();
+        let meta = corpus_meta();
+        let data_dir = meta.data_dir_string();
+        let full = fs
+            .read_shard(2, "bucket", "testobj", &data_dir, 1)
+            .unwrap()
+            .unwrap();
+        let mock = MockShardReader {
+            shards: vec![None, None, Some(full.clone())],
+        };
+
+        let readers: Vec<Box<dyn ShardReader>> = vec![
+            Box::new(corpus_reader()),
+            Box::new(mock),
+            #[cfg(feature = "mmap")]
+            Box::new(MmapShardReader::new(fs.disk_paths.clone())),
+        ];
+        let len = full.len() as u64;
+        for reader in &readers {
+            let range = |offset, n| {
+                reader
+                    .read_shard_range(2, "bucket", "testobj", &data_dir, 1, offset, n)
+                    .unwrap()
+                    .unwrap()
+            };
+            assert_eq!(range(100, 64), &full[100..164]);
+            // Ranges running past the end are clipped
+            assert_eq!(range(len - 10, 64), &full[full.len() - 10..]);
+            assert!(range(len + 5, 64).is_empty());
+
+            let shard_len = reader.shard_len(2, "bucket", "testobj", &data_dir, 1);
+            assert_eq!(shard_len.unwrap(), Some(len));
+            let missing = reader.read_shard_range(0, "bucket", "missing", &data_dir, 1, 0, 8);
+            assert!(missing.unwrap().is_none());
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_shard_reader_matches_fs_reader() {
+        let fs = corpus_reader();
+        let mmap = MmapShardReader::new(fs.disk_paths.clone());
+        let meta = corpus_meta();
+        let data_dir = meta.data_dir_string();
+
+        let map = mmap
+            .map_shard(2, "bucket", "testobj", &data_dir, 1)
+            .unwrap()
+            .unwrap();
+        let block = shard::shard_block_slice(&map, 0, meta.shard_size(), true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.len(), 214840);
+        // Later reads reuse the map
+        let again = mmap
+            .map_shard(2, "bucket", "testobj", &data_dir, 1)
+            .unwrap()
+            .unwrap();
+        assert!(std::sync::Arc::ptr_eq(&map, &again));
+        assert!(mmap
+            .map_shard(2, "bucket", "missing", &data_dir, 1)
+            .unwrap()
+            .is_none());
+
+        assert_eq!(
+            decode_object(&mmap, &meta, &[1]).unwrap(),
+            decode_object(&fs, &meta, &[]).unwrap()
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_cache_is_bounded_and_revalidated() {
+        use crate::sink::{FsShardSink, ShardSink};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let disk_paths = vec![tmp.path().display().to_string()];
+        let sink = FsShardSink {
+            disk_paths: disk_paths.clone(),
+        };
+        for part in 1..=2 {
+            sink.write_shard(0, "bucket", "key", "dir", part, b"shard")
+                .unwrap();
+        }
+        let map = |reader: &MmapShardReader, part| {
+            reader
+                .map_shard(0, "bucket", "key", "dir", part)
+                .unwrap()
+                .unwrap()
+        };
+
+        // Only the most recently used map is kept
+        let reader = MmapShardReader::with_max_maps(disk_paths.clone(), 1);
+        let first = map(&reader, 1);
+        assert!(std::sync::Arc::ptr_eq(&first, &map(&reader, 1)));
+        map(&reader, 2);
+        assert!(!std::sync::Arc::ptr_eq(&first, &map(&reader, 1)));
+
+        // A replaced file is mapped again, while old maps stay readable
+        let reader = MmapShardReader::new(disk_paths);
+        let old = map(&reader, 1);
+        sink.write_shard(0, "bucket", "key", "dir", 1, b"healed shard")
+            .unwrap();
+        assert_eq!(&map(&reader, 1)[..], b"healed shard");
+        assert_eq!(&old[..], b"shard");
+    }
+
+    #[test]
+    fn test_fs_shard_len_matches_layout() {
+        use crate::layout::{check_shard_sizes, ShardSizeStatus};
+
+        let reader = corpus_reader();
+        let xl = std::fs::read(format!("{}/bucket/testobj/xl.meta", reader.disk_paths[1])).unwrap();
+        let meta = crate::xlmeta::parse(&xl).unwrap();
+        let data_dir = meta.data_dir_string();
+
+        let checks = check_shard_sizes(&meta, |disk, part| {
+            reader.shard_len(disk, "bucket", "testobj", &data_dir, part)
+        });
+        assert_eq!(checks.len(), 5);
+        assert!(checks.iter().all(|c| c.status == ShardSizeStatus::Ok));
+
+        let checks = check_shard_sizes(&meta, |disk, part| {
+            reader.shard_len(disk, "bucket", "missing", &data_dir, part)
+        });
+        assert!(checks.iter().all(|c| c.status == ShardSizeStatus::Missing));
+    }
+
+    #[test]
+    fn test_decode_object_version_older_version() {
+        // Only disk4 still lists the older version next to the latest one
+        let reader = corpus_reader();
+        let result = decode_object_version(
+            &reader,
+            5,
+            "bucket",
+            "testobj",
+            "50051050-62bc-4928-b0d3-65f4f828a0c7",
+            &[],
+        )
+        .unwrap();
+
+        match result {
+            VersionedObject::Object { meta, data } => {
+                assert_eq!(meta.key, "testobj");
+                assert_eq!(data.len(), 644520);
+            }
+            other => panic!("expected object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_object_version_not_found() {
+        let reader = corpus_reader();
+        let result = decode_object_version(&reader, 5, "bucket", "testobj", "null", &[]).unwrap();
+        assert!(matches!(result, VersionedObject::NotFound));
+
+        let result = decode_object_version(&reader, 5, "bucket", "missing", "null", &[]).unwrap();
+        assert!(matches!(result, VersionedObject::NotFound));
+
+        // An xl.meta that exists but cannot be parsed is an error
+        let tmp = tempfile::tempdir().unwrap();
+        let disk = tmp.path().display().to_string();
+        std::fs::create_dir_all(format!("{}/bucket/broken", disk)).unwrap();
+        std::fs::write(format!("{}/bucket/broken/xl.meta", disk), b"garbage").unwrap();
+        let reader = FsShardReader {
+            disk_paths: vec![disk],
+        };
+        assert!(decode_object_version(&reader, 1, "bucket", "broken", "null", &[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_object_version_inline_data() {
+        // Only xl.meta files exist; the shards live in their inline data
+        let data: Vec<u8> = (0..700u32).map(|i| (i % 251) as u8).collect();
+        let obj = crate::encode_object("bucket", "small", &data, 2, 2, 256).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let disk_paths: Vec<String> = (0..4)
+            .map(|i| format!("{}/disk{}", tmp.path().display(), i))
+            .collect();
+        for (disk, path) in disk_paths.iter().enumerate() {
+            let mut meta = obj.meta.clone();
+            meta.erasure_index = meta.distribution[disk] as usize;
+            let xl = xlmeta::encode_inline(&meta, &obj.shards[disk]).unwrap();
+            std::fs::create_dir_all(format!("{}/bucket/small", path)).unwrap();
+            std::fs::write(format!("{}/bucket/small/xl.meta", path), xl).unwrap();
+        }
+
+        let reader = FsShardReader { disk_paths };
+        for skip in [&[][..], &[0, 3]] {
+            match decode_object_version(&reader, 4, "bucket", "small", "null", skip).unwrap() {
+                VersionedObject::Object { data: got, .. } => assert!(got == data),
+                other => panic!("expected object, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_decode_with_mock_reader_all_present() {
         use crate::shard::{highway_key, HASH_SIZE};
@@ -317,6 +1525,7 @@ mod tests {
             number: 1,
             size: meta.size,
             actual_size: meta.size,
+            ..Default::default()
         }];
 
         let result = decode_object(&reader, &meta, &[]).unwrap();
@@ -324,4 +1533,394 @@ mod tests {
         assert_eq!(&result[0..4], &shard0_data);
         assert_eq!(&result[4..8], &shard1_data);
     }
+
+    #[test]
+    fn test_decode_legacy_object_from_disk() {
+        use crate::layout::{check_shard_sizes, ShardSizeStatus};
+        use crate::sink::{FsShardSink, ShardSink};
+
+        // Legacy objects keep their parts in <key>/legacy/part.N
+        let data: Vec<u8> = (0..3000u32).map(|i| (i % 253) as u8).collect();
+        let obj = crate::encode_object("bucket", "old", &data, 2, 2, 1000).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let disk_paths: Vec<String> = (0..4)
+            .map(|i| format!("{}/disk{}", tmp.path().display(), i))
+            .collect();
+        let sink = FsShardSink {
+            disk_paths: disk_paths.clone(),
+        };
+        for (disk, shard) in obj.shards.iter().enumerate() {
+            sink.write_shard(disk, "bucket", "old", "legacy", 1, shard)
+                .unwrap();
+        }
+
+        let meta = ObjectMeta {
+            version_type: VersionType::Legacy,
+            data_dir: Uuid16::default(),
+            legacy_data_dir: true,
+            ..obj.meta.clone()
+        };
+        let reader = FsShardReader { disk_paths };
+        assert!(decode_object(&reader, &meta, &[1]).unwrap() == data);
+
+        let checks = check_shard_sizes(&meta, |disk, part| {
+            reader.shard_len(disk, "bucket", "old", &meta.data_dir_string(), part)
+        });
+        assert_eq!(checks.len(), 4);
+        assert!(checks.iter().all(|c| c.status == ShardSizeStatus::Ok));
+    }
+
+    #[test]
+    fn test_decode_whole_file_bitrot_rejects_corrupt_shard() {
+        let data_blocks = 2;
+        let parity_blocks = 1;
+        let mut shards = vec![vec![1u8, 2, 3, 4], vec![5u8, 6, 7, 8], vec![0u8; 4]];
+        ReedSolomon::new(data_blocks, parity_blocks)
+            .unwrap()
+            .encode(&mut shards)
+            .unwrap();
+
+        let mut meta = ObjectMeta {
+            bucket: "test".to_string(),
+            key: "key".to_string(),
+            data_blocks,
+            parity_blocks,
+            block_size: 8,
+            size: 8,
+            distribution: vec![1, 2, 3],
+            erasure_index: 1,
+            bitrot_algo: BitrotAlgorithm::Sha256,
+            ..Default::default()
+        };
+        meta.parts = vec![PartMeta {
+            number: 1,
+            size: 8,
+            actual_size: 8,
+            checksum: shard::bitrot_hash(BitrotAlgorithm::Sha256, &shards[0]),
+        }];
+
+        // Whole-file layout: the shard file is the plain data
+        let mut stored: Vec<Option<Vec<u8>>> = shards.iter().cloned().map(Some).collect();
+        let reader = MockShardReader {
+            shards: stored.clone(),
+        };
+        let result = decode_object(&reader, &meta, &[]).unwrap();
+        assert_eq!(result, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Corrupt disk 0's shard: its checksum mismatch forces reconstruction
+        stored[0].as_mut().unwrap()[0] ^= 0xFF;
+        let reader = MockShardReader { shards: stored };
+        let result = decode_object(&reader, &meta, &[]).unwrap();
+        assert_eq!(result, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_decode_reads_checksum_copies_once_per_object() {
+        /// Reader counting `read_xlmeta` calls per disk
+        struct XlMetaCounter {
+            inner: MockShardReader,
+            reads: std::cell::RefCell<Vec<usize>>,
+        }
+
+        impl ShardReader for XlMetaCounter {
+            fn read_shard(
+                &self,
+                disk_index: usize,
+                bucket: &str,
+                key: &str,
+                data_dir: &str,
+                part_number: i32,
+            ) -> Result<Option<Vec<u8>>> {
+                self.inner
+                    .read_shard(disk_index, bucket, key, data_dir, part_number)
+            }
+
+            fn read_xlmeta(&self, disk_index: usize, _: &str, _: &str) -> Result<Option<Vec<u8>>> {
+                self.reads.borrow_mut()[disk_index] += 1;
+                Ok(None)
+            }
+        }
+
+        let mut shards = vec![vec![1u8, 2, 3, 4], vec![5u8, 6, 7, 8], vec![0u8; 4]];
+        ReedSolomon::new(2, 1).unwrap().encode(&mut shards).unwrap();
+        let meta = ObjectMeta {
+            bucket: "test".to_string(),
+            key: "key".to_string(),
+            data_blocks: 2,
+            parity_blocks: 1,
+            block_size: 8,
+            size: 16,
+            distribution: vec![1, 2, 3],
+            erasure_index: 1,
+            bitrot_algo: BitrotAlgorithm::Sha256,
+            parts: (1..=2)
+                .map(|number| PartMeta {
+                    number,
+                    size: 8,
+                    actual_size: 8,
+                    checksum: shard::bitrot_hash(BitrotAlgorithm::Sha256, &shards[0]),
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // Own shard's checksums come from meta; the other disks' copies are
+        // read once for both parts
+        let reader = XlMetaCounter {
+            inner: MockShardReader {
+                shards: shards.iter().cloned().map(Some).collect(),
+            },
+            reads: std::cell::RefCell::new(vec![0; 3]),
+        };
+        let data = decode_object(&reader, &meta, &[2]).unwrap();
+        assert_eq!(data, [&shards[0][..], &shards[1][..]].concat().repeat(2));
+        assert_eq!(*reader.reads.borrow(), vec![0, 1, 0]);
+
+        let checksums = ShardChecksums::own(&meta);
+        assert_eq!(checksums.part(2)[0], Some(meta.parts[1].checksum.clone()));
+        assert_eq!(checksums.part(2)[1], None);
+        assert!(checksums.part(3).is_empty());
+        assert_eq!(checksums.missing_disks(&meta, &[]), vec![1, 2]);
+
+        // A copy that is not this version is ignored
+        let mut checksums = checksums;
+        checksums.add_copy(&meta, 1, b"not xl.meta");
+        assert_eq!(checksums.part(1)[1], None);
+
+        // Streaming bitrot keeps its hashes in the shards
+        let streaming = ObjectMeta {
+            bitrot_algo: BitrotAlgorithm::HighwayHash256S,
+            ..meta
+        };
+        let checksums = ShardChecksums::own(&streaming);
+        assert!(checksums.part(1).is_empty());
+        assert!(checksums.missing_disks(&streaming, &[]).is_empty());
+    }
+
+    /// Reader counting `read_shard` calls per disk
+    struct CountingShardReader {
+        inner: MockShardReader,
+        reads: std::cell::RefCell<Vec<usize>>,
+    }
+
+    impl ShardReader for CountingShardReader {
+        fn read_shard(
+            &self,
+            disk_index: usize,
+            bucket: &str,
+            key: &str,
+            data_dir: &str,
+            part_number: i32,
+        ) -> Result<Option<Vec<u8>>> {
+            self.reads.borrow_mut()[disk_index] += 1;
+            self.inner
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+        }
+    }
+
+    #[test]
+    fn test_decode_reads_each_shard_once_per_part() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let files = encode_shard_files(&data, 2, 2, 64);
+
+        let mut meta = ObjectMeta {
+            bucket: "test".to_string(),
+            key: "key".to_string(),
+            data_blocks: 2,
+            parity_blocks: 2,
+            block_size: 64,
+            size: data.len() as i64,
+            distribution: vec![1, 2, 3, 4],
+            ..Default::default()
+        };
+        meta.parts = vec![PartMeta {
+            number: 1,
+            size: meta.size,
+            actual_size: meta.size,
+            ..Default::default()
+        }];
+
+        // All data shards present: parity is never read
+        let reader = CountingShardReader {
+            inner: MockShardReader {
+                shards: files.iter().cloned().map(Some).collect(),
+            },
+            reads: std::cell::RefCell::new(vec![0; 4]),
+        };
+        assert_eq!(decode_object(&reader, &meta, &[]).unwrap(), data);
+        assert_eq!(*reader.reads.borrow(), vec![1, 1, 0, 0]);
+
+        // One data shard lost: parity is read once and reused for every block
+        let reader = CountingShardReader {
+            inner: MockShardReader {
+                shards: files.iter().cloned().map(Some).collect(),
+            },
+            reads: std::cell::RefCell::new(vec![0; 4]),
+        };
+        assert_eq!(decode_object(&reader, &meta, &[0]).unwrap(), data);
+        assert_eq!(*reader.reads.borrow(), vec![0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_decode_object_range_multipart() {
+        let (data, meta, reader) = multipart_object();
+        assert_eq!(decode_object(&reader, &meta, &[]).unwrap(), data);
+
+        // Within a block, across blocks, across the part boundary, to the end
+        for (offset, len) in [(5, 10), (60, 10), (250, 120), (0, 700), (650, 100)] {
+            let expected = &data[offset..std::cmp::min(offset + len, data.len())];
+            for skip in [&[][..], &[0], &[2]] {
+                let got =
+                    decode_object_range(&reader, &meta, offset as i64, len as i64, skip).unwrap();
+                assert!(got == expected, "range {}+{} skip {:?}", offset, len, skip);
+            }
+        }
+
+        // Only the data shards of the one block holding the range are read
+        reader.range_reads.store(0, Ordering::Relaxed);
+        let got = decode_object_range(&reader, &meta, 310, 20, &[]).unwrap();
+        assert_eq!(got, &data[310..330]);
+        assert_eq!(reader.range_reads.load(Ordering::Relaxed), 2);
+
+        assert!(decode_object_range(&reader, &meta, 700, 10, &[])
+            .unwrap()
+            .is_empty());
+        assert!(decode_object_range(&reader, &meta, 701, 1, &[]).is_err());
+        assert!(decode_object_range(&reader, &meta, -1, 1, &[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_object_range_cicd_corpus() {
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let full = decode_object(&reader, &meta, &[]).unwrap();
+
+        for (offset, len) in [(0, 100), (300000, 20), (600000, 50000)] {
+            let got = decode_object_range(&reader, &meta, offset as i64, len, &[0, 3]).unwrap();
+            let end = std::cmp::min(offset + len as usize, full.len());
+            assert!(got == full[offset..end], "range {}+{}", offset, len);
+        }
+    }
+
+    #[test]
+    fn test_decode_trims_padding_of_every_block() {
+        // 3 data shards of ceil(100 / 3) = 34 bytes carry 2 padding bytes per block
+        let data: Vec<u8> = (0..450u32).map(|i| (i * 11 % 241) as u8).collect();
+        let files = encode_shard_files(&data, 3, 2, 100);
+        let mut meta = ObjectMeta {
+            bucket: "test".to_string(),
+            key: "key".to_string(),
+            data_blocks: 3,
+            parity_blocks: 2,
+            block_size: 100,
+            size: data.len() as i64,
+            distribution: vec![1, 2, 3, 4, 5],
+            ..Default::default()
+        };
+        meta.parts = vec![PartMeta {
+            number: 1,
+            size: meta.size,
+            actual_size: meta.size,
+            ..Default::default()
+        }];
+        let reader = MockShardReader {
+            shards: files.into_iter().map(Some).collect(),
+        };
+
+        for skip in [&[][..], &[0], &[1, 2]] {
+            let got = decode_object(&reader, &meta, skip).unwrap();
+            assert!(got == data, "skip {:?}", skip);
+        }
+    }
+
+    #[test]
+    fn test_decode_object_to_writer_matches_decode_object() {
+        let (data, meta, reader) = multipart_object();
+        for skip in [&[][..], &[1]] {
+            let mut out = Vec::new();
+            let written = decode_object_to_writer(&reader, &meta, skip, &mut out).unwrap();
+            assert_eq!(written, data.len() as u64);
+            assert!(out == data, "skip {:?}", skip);
+        }
+
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let mut out = Vec::new();
+        decode_object_to_writer(&reader, &meta, &[2, 4], &mut out).unwrap();
+        assert!(out == decode_object(&reader, &meta, &[]).unwrap());
+
+        // Lost shards beyond parity fail instead of writing garbage
+        let mut out = Vec::new();
+        assert!(decode_object_to_writer(&reader, &meta, &[0, 1, 2], &mut out).is_err());
+    }
+
+    /// Reader failing with an I/O error on one disk
+    struct FailingDiskReader<'a> {
+        inner: &'a dyn ShardReader,
+        failing_disk: usize,
+    }
+
+    impl ShardReader for FailingDiskReader<'_> {
+        fn read_shard(
+            &self,
+            disk_index: usize,
+            bucket: &str,
+            key: &str,
+            data_dir: &str,
+            part_number: i32,
+        ) -> Result<Option<Vec<u8>>> {
+            if disk_index == self.failing_disk {
+                bail!("input/output error");
+            }
+            self.inner
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+        }
+    }
+
+    #[test]
+    fn test_decode_object_with_report_per_disk_and_block() {
+        use crate::diagnostics::ShardIssue;
+
+        // Distribution [2, 3, 1]: disk 0 holds data shard 2, disk 1 parity,
+        // disk 2 data shard 1
+        let (data, meta, mut reader) = multipart_object();
+        let stride = shard::HASH_SIZE + meta.shard_size() as usize;
+        reader.parts[0][0].as_mut().unwrap()[2 * stride + shard::HASH_SIZE] ^= 0xFF;
+        reader.parts[1][2] = None;
+
+        let mut report = DecodeReport::default();
+        let got = decode_object_with_report(&reader, &meta, &[], &mut report).unwrap();
+        assert_eq!(got, data);
+        assert_eq!(report.failing_disks(), vec![0, 2]);
+
+        let disk0: Vec<_> = report.for_disk(0).collect();
+        assert_eq!(disk0.len(), 1);
+        assert_eq!((disk0[0].part_number, disk0[0].block), (1, 2));
+        assert!(matches!(disk0[0].issue, ShardIssue::BitrotFailed(_)));
+        assert!(disk0[0].reconstructed);
+
+        // Every block of part 2 (400 bytes in 64-byte blocks) lacks disk 2
+        let disk2: Vec<_> = report.for_disk(2).collect();
+        assert_eq!(disk2.len(), 7);
+        assert!(disk2
+            .iter()
+            .all(|d| d.part_number == 2 && d.issue == ShardIssue::Missing && d.reconstructed));
+
+        // Skipped data disk plus unreadable parity: nothing is reconstructed
+        let (_, meta, reader) = multipart_object();
+        let failing = FailingDiskReader {
+            inner: &reader,
+            failing_disk: 1,
+        };
+        let mut report = DecodeReport::default();
+        assert!(decode_object_with_report(&failing, &meta, &[0], &mut report).is_err());
+        assert_eq!(report.failing_disks(), vec![1]);
+        assert_eq!(
+            report.for_disk(0).next().unwrap().issue,
+            ShardIssue::Skipped
+        );
+        let parity = report.for_disk(1).next().unwrap();
+        assert!(matches!(&parity.issue, ShardIssue::Unreadable(e) if e.contains("input/output")));
+        assert!(report.shards.iter().all(|d| !d.reconstructed));
+    }
 }
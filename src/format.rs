@@ -4,13 +4,15 @@
 
 use std::collections::HashMap;
 
-use anyhow::{bail, Result};
-use serde::Deserialize;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
 
+use crate::distribution::DistributionAlgo;
 use crate::types::{ClusterConfig, DiskInfo, PoolConfig};
 
 /// Raw format.json structure from MinIO
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiskFormat {
     pub version: String,
     pub format: String,
@@ -18,19 +20,61 @@ pub struct DiskFormat {
     pub xl: XLFormat,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XLFormat {
     pub version: String,
     pub this: String, // This disk's UUID
     pub sets: Vec<Vec<String>>,
-    #[serde(rename = "distributionAlgo")]
+    /// Absent before format.json v3, meaning CRCMOD
+    #[cfg_attr(feature = "serde", serde(rename = "distributionAlgo", default))]
     pub distribution_algo: String,
 }
 
 /// Parse a format.json from bytes
 pub fn parse_format(data: &[u8]) -> Result<DiskFormat> {
-    let format: DiskFormat = serde_json::from_slice(data)?;
-    Ok(format)
+    let json: Value = serde_json::from_slice(data)?;
+    let xl = json.get("xl").context("missing field \"xl\"")?;
+    let sets = xl
+        .get("sets")
+        .and_then(Value::as_array)
+        .context("missing field \"xl.sets\"")?
+        .iter()
+        .map(|set| {
+            set.as_array()
+                .context("erasure set is not an array")?
+                .iter()
+                .map(|disk| {
+                    disk.as_str()
+                        .map(str::to_string)
+                        .context("disk id is not a string")
+                })
+                .collect()
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(DiskFormat {
+        version: string_field(&json, "version")?,
+        format: string_field(&json, "format")?,
+        id: string_field(&json, "id")?,
+        xl: XLFormat {
+            version: string_field(xl, "version")?,
+            this: string_field(xl, "this")?,
+            sets,
+            distribution_algo: match xl.get("distributionAlgo") {
+                None => String::new(),
+                Some(_) => string_field(xl, "distributionAlgo")?,
+            },
+        },
+    })
+}
+
+/// String member `name` of a JSON object
+fn string_field(json: &Value, name: &str) -> Result<String> {
+    json.get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .with_context(|| format!("missing or non-string field {:?}", name))
 }
 
 /// Build cluster config from multiple format.json files.
@@ -68,6 +112,30 @@ pub fn build_cluster_config(formats: &[(usize, DiskFormat)]) -> Result<ClusterCo
             bail!("pool {} has no erasure sets", pool_id);
         }
 
+        // Every disk of the pool must place objects the same way
+        let distribution_algo: DistributionAlgo = disks_in_pool[0]
+            .1
+            .xl
+            .distribution_algo
+            .parse()
+            .with_context(|| format!("pool {}", pool_id))?;
+        for (_, fmt) in disks_in_pool {
+            let algo: DistributionAlgo = fmt
+                .xl
+                .distribution_algo
+                .parse()
+                .with_context(|| format!("pool {} disk {}", pool_id, fmt.xl.this))?;
+            if algo != distribution_algo {
+                bail!(
+                    "pool {}: disk {} uses distribution algorithm {:?}, expected {:?}",
+                    pool_id,
+                    fmt.xl.this,
+                    algo,
+                    distribution_algo
+                );
+            }
+        }
+
         // Build UUID to device_id mapping
         let mut uuid_to_device: HashMap<String, usize> = HashMap::new();
         for (device_id, fmt) in disks_in_pool {
@@ -97,6 +165,7 @@ pub fn build_cluster_config(formats: &[(usize, DiskFormat)]) -> Result<ClusterCo
             pool_id: pool_id.clone(),
             pool_index: pool_idx,
             sets,
+            distribution_algo,
         });
     }
 
@@ -135,6 +204,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_format_rejects_missing_fields() {
+        let json = r#"{"version": "1", "format": "xl", "id": "pool-123", "xl": {"version": "3"}}"#;
+        assert!(parse_format(json.as_bytes()).is_err());
+
+        let json = r#"{"version": "1", "format": "xl", "id": "pool-123",
+            "xl": {"version": "3", "this": "a", "sets": [["a", 7]]}}"#;
+        assert!(parse_format(json.as_bytes()).is_err());
+    }
+
     #[test]
     fn test_build_cluster_config_empty_formats() {
         let result = build_cluster_config(&[]);
@@ -183,5 +262,36 @@ mod tests {
             .find(|d| d.uuid == "disk-c")
             .unwrap();
         assert_eq!(disk_c.device_id, None);
+        assert_eq!(
+            cluster.pools[0].distribution_algo,
+            DistributionAlgo::SipModParity
+        );
+    }
+
+    #[test]
+    fn test_build_cluster_config_distribution_algo() {
+        let format = |this: &str, algo: Option<&str>| {
+            let algo = algo
+                .map(|a| format!(r#", "distributionAlgo": "{}""#, a))
+                .unwrap_or_default();
+            let json = format!(
+                r#"{{"version": "1", "format": "xl", "id": "pool-1",
+                    "xl": {{"version": "2", "this": "{}", "sets": [["a", "b"]]{}}}}}"#,
+                this, algo
+            );
+            parse_format(json.as_bytes()).unwrap()
+        };
+
+        // Older format.json files have no algorithm: CRCMOD
+        let cluster = build_cluster_config(&[(0, format("a", None)), (1, format("b", None))]);
+        assert_eq!(
+            cluster.unwrap().pools[0].distribution_algo,
+            DistributionAlgo::CrcMod
+        );
+
+        let mixed = [(0, format("a", Some("SIPMOD"))), (1, format("b", None))];
+        assert!(build_cluster_config(&mixed).is_err());
+        let bogus = [(0, format("a", Some("SIPHASH")))];
+        assert!(build_cluster_config(&bogus).is_err());
     }
 }
@@ -0,0 +1,290 @@
+//! Heal: regenerate one disk's copy of an object
+//!
+//! Like MinIO heal after a disk replacement: every block of the target disk's
+//! shard is recomputed from the other disks with Reed-Solomon, streamed out
+//! with fresh bitrot hashes, and the version is added to the disk's xl.meta.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use reed_solomon_erasure::galois_8::ReedSolomon;
+
+use crate::erasure::{PartShards, ShardChecksums, ShardReader};
+use crate::layout::ShardLayout;
+use crate::shard::ShardWriter;
+use crate::sink::ShardSink;
+use crate::types::ObjectMeta;
+use crate::xlmeta;
+
+/// Rebuild the shard files and xl.meta of `disk_index` for one object version,
+/// returning the shard bytes written.
+///
+/// Only the target disk's xl.meta is read, never its shards. Each block is
+/// rebuilt from any `data_blocks` intact shards on the other disks; data and
+/// parity shards can both be regenerated, and are written block by block.
+/// The version's metadata is taken from another disk's xl.meta, given the
+/// target disk's EcIndex and merged into the target's existing xl.meta (a
+/// missing or unparseable copy is replaced), so versions whose parts the disk
+/// does not hold are never added. Each part file replaces the existing one only
+/// once complete, and xl.meta is written after all parts, so a failure leaves
+/// the files not yet rebuilt as they were.
+/// Only streaming bitrot (HighwayHash256S) objects without inline data can be healed.
+pub fn heal_disk(
+    reader: &dyn ShardReader,
+    sink: &dyn ShardSink,
+    meta: &ObjectMeta,
+    disk_index: usize,
+) -> Result<u64> {
+    ensure!(
+        meta.bitrot_algo.is_streaming(),
+        "cannot heal {:?} (whole-file bitrot) objects",
+        meta.bitrot_algo
+    );
+    let total_shards = meta.total_shards();
+    let shard_idx = match meta.distribution.get(disk_index) {
+        Some(&e) if e >= 1 && e as usize <= total_shards => e as usize - 1,
+        _ => bail!("disk {} holds no shard of the object", disk_index),
+    };
+
+    // Build the xl.meta copy first so nothing is written if there is none
+    let donor = donor_xlmeta(reader, meta, disk_index)?;
+    let target = reader
+        .read_xlmeta(disk_index, &meta.bucket, &meta.key)
+        .with_context(|| format!("read xl.meta of disk {}", disk_index))?
+        .filter(|data| xlmeta::parse_full(data).is_ok());
+    let xl = xlmeta::merge_version(target.as_deref(), &donor, &meta.version_id, disk_index)
+        .context("merge xl.meta")?;
+
+    let rs = ReedSolomon::new(meta.data_blocks, meta.parity_blocks)
+        .map_err(|e| anyhow!("create RS encoder: {:?}", e))?;
+    let shard_size = meta.shard_size();
+    let data_dir = meta.data_dir_string();
+    let skip = [disk_index];
+    let checksums = ShardChecksums::read(reader, meta, &skip);
+    let mut written = 0u64;
+
+    for part in &meta.parts {
+        let layout = ShardLayout::for_part_size(meta, part.size);
+        let mut shards =
+            PartShards::new(reader, meta, part.number, &skip, Some(layout), &checksums);
+        let open = || -> Result<_> {
+            let file = sink
+                .create_shard(disk_index, &meta.bucket, &meta.key, &data_dir, part.number)
+                .with_context(|| format!("create part {} on disk {}", part.number, disk_index))?;
+            Ok(ShardWriter::new(file, shard_size))
+        };
+        // Opened once the first block is rebuilt
+        let mut writer = None;
+
+        for block in layout.blocks() {
+            shards.load(0..total_shards, block.index);
+            let mut blocks: Vec<Option<Vec<u8>>> = (0..total_shards)
+                .map(|i| {
+                    shards.block(i, block.index, shard_size).map(|b| {
+                        let mut v = b.to_vec();
+                        v.resize(block.data_size as usize, 0);
+                        v
+                    })
+                })
+                .collect();
+            let available = blocks.iter().filter(|b| b.is_some()).count();
+            if available < meta.data_blocks {
+                bail!(
+                    "part {} block {}: insufficient shards: have {}, need {}",
+                    part.number,
+                    block.index,
+                    available,
+                    meta.data_blocks
+                );
+            }
+            rs.reconstruct(&mut blocks).map_err(|e| {
+                anyhow!(
+                    "part {} block {}: reconstruct: {:?}",
+                    part.number,
+                    block.index,
+                    e
+                )
+            })?;
+            let shard = blocks[shard_idx].as_deref().context("shard not rebuilt")?;
+            let writer = match &mut writer {
+                Some(writer) => writer,
+                None => writer.insert(open()?),
+            };
+            writer
+                .write_block(shard)
+                .with_context(|| format!("write part {} to disk {}", part.number, disk_index))?;
+        }
+
+        // Empty parts have no blocks but still get an (empty) file
+        let writer = match writer {
+            Some(writer) => writer,
+            None => open()?,
+        };
+        let (file, size) = writer
+            .finish()
+            .with_context(|| format!("write part {} to disk {}", part.number, disk_index))?;
+        file.commit()
+            .with_context(|| format!("commit part {} on disk {}", part.number, disk_index))?;
+        written += size;
+    }
+
+    sink.write_xlmeta(disk_index, &meta.bucket, &meta.key, &xl)
+        .with_context(|| format!("write xl.meta to disk {}", disk_index))?;
+    Ok(written)
+}
+
+/// First readable xl.meta copy, other than the target's, holding the version
+fn donor_xlmeta(reader: &dyn ShardReader, meta: &ObjectMeta, disk_index: usize) -> Result<Vec<u8>> {
+    for disk in (0..meta.distribution.len()).filter(|&d| d != disk_index) {
+        let Ok(Some(data)) = reader.read_xlmeta(disk, &meta.bucket, &meta.key) else {
+            continue;
+        };
+        let Ok(parsed) = xlmeta::parse_full(&data) else {
+            continue;
+        };
+        let Some(version) = parsed.find_version(&meta.version_id) else {
+            continue;
+        };
+        ensure!(
+            !version.header.has_inline_data(),
+            "cannot heal objects with inline data"
+        );
+        return Ok(data);
+    }
+    bail!("no other disk has an xl.meta copy of the version")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erasure::decode_object;
+    use crate::sink::FsShardSink;
+    use crate::testutil::{corpus_meta, corpus_reader};
+
+    #[test]
+    fn test_heal_matches_original_disk() {
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FsShardSink {
+            disk_paths: (0..5)
+                .map(|i| format!("{}/disk{}", dir.path().display(), i))
+                .collect(),
+        };
+        let part = crate::shard::shard_path("bucket", "testobj", &meta.data_dir_string(), 1);
+
+        for disk in 0..5 {
+            let written = heal_disk(&reader, &sink, &meta, disk).unwrap();
+
+            // Data and parity shards come out identical to the originals
+            let original = std::fs::read(format!("{}/{}", reader.disk_paths[disk], part)).unwrap();
+            let healed = std::fs::read(format!("{}/{}", sink.disk_paths[disk], part)).unwrap();
+            assert!(healed == original, "disk {}", disk);
+            assert_eq!(written, original.len() as u64);
+
+            let xl = std::fs::read(format!("{}/bucket/testobj/xl.meta", sink.disk_paths[disk]));
+            let xl = xlmeta::parse_full(&xl.unwrap()).unwrap();
+            let healed_meta = &xl.find_version(&meta.version_id).unwrap().meta;
+            assert_eq!(healed_meta.erasure_index, meta.distribution[disk] as usize);
+            assert_eq!(healed_meta.size, meta.size);
+
+            // Versions already on the disk are kept, latest first
+            let original = reader.read_xlmeta(disk, "bucket", "testobj").unwrap();
+            let mut expected: Vec<_> = original
+                .map(|data| xlmeta::parse_full(&data).unwrap().versions)
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.header.version_id)
+                .filter(|id| *id != meta.version_id)
+                .collect();
+            expected.push(meta.version_id);
+            let ids: Vec<_> = xl.versions.iter().map(|v| v.header.version_id).collect();
+            assert_eq!(ids, expected, "disk {}", disk);
+        }
+
+        // A set of healed disks decodes on its own
+        let healed = crate::erasure::FsShardReader {
+            disk_paths: sink.disk_paths.clone(),
+        };
+        assert_eq!(
+            decode_object(&healed, &meta, &[0, 1]).unwrap(),
+            decode_object(&reader, &meta, &[]).unwrap()
+        );
+    }
+
+    /// Corpus reader exposing only the xl.meta of one disk
+    struct OneXlMeta {
+        inner: crate::erasure::FsShardReader,
+        disk: usize,
+    }
+
+    impl ShardReader for OneXlMeta {
+        fn read_shard(
+            &self,
+            disk_index: usize,
+            bucket: &str,
+            key: &str,
+            data_dir: &str,
+            part_number: i32,
+        ) -> Result<Option<Vec<u8>>> {
+            self.inner
+                .read_shard(disk_index, bucket, key, data_dir, part_number)
+        }
+
+        fn read_xlmeta(
+            &self,
+            disk_index: usize,
+            bucket: &str,
+            key: &str,
+        ) -> Result<Option<Vec<u8>>> {
+            if disk_index != self.disk {
+                return Ok(None);
+            }
+            self.inner.read_xlmeta(disk_index, bucket, key)
+        }
+    }
+
+    #[test]
+    fn test_heal_copies_only_the_healed_version() {
+        // disk4's xl.meta also lists a newer version, stored in another data dir
+        let reader = OneXlMeta {
+            inner: corpus_reader(),
+            disk: 3,
+        };
+        let meta = corpus_meta();
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FsShardSink {
+            disk_paths: vec![dir.path().display().to_string(); 5],
+        };
+        heal_disk(&reader, &sink, &meta, 0).unwrap();
+
+        let xl = std::fs::read(dir.path().join("bucket/testobj/xl.meta")).unwrap();
+        let xl = xlmeta::parse_full(&xl).unwrap();
+        assert_eq!(xl.versions.len(), 1);
+        assert_eq!(xl.versions[0].meta.version_id, meta.version_id);
+        assert_eq!(
+            xl.versions[0].meta.erasure_index,
+            meta.distribution[0] as usize
+        );
+    }
+
+    #[test]
+    fn test_heal_needs_enough_shards() {
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FsShardSink {
+            disk_paths: vec![dir.path().display().to_string(); 5],
+        };
+        let broken = crate::erasure::FsShardReader {
+            disk_paths: vec![
+                String::new(),
+                reader.disk_paths[1].clone(),
+                String::new(),
+                String::new(),
+                reader.disk_paths[4].clone(),
+            ],
+        };
+        let err = heal_disk(&broken, &sink, &meta, 0).unwrap_err();
+        assert!(err.to_string().contains("insufficient shards"), "{:#}", err);
+        assert!(!dir.path().join("bucket").exists());
+    }
+}
@@ -0,0 +1,292 @@
+//! Shard file geometry
+//!
+//! Derives where every block of a part lives inside `part.N` from the erasure
+//! parameters in xl.meta. The layout is the same on every disk of the set.
+//!
+//! Streaming bitrot (HighwayHash256S):
+//! ```text
+//! block i: [32-byte hash][shard data]   at offset i * (32 + shard_size)
+//! ```
+//! Whole-file bitrot: blocks are stored back to back without hashes.
+
+use std::cmp::Ordering;
+
+use anyhow::{Context, Result};
+
+use crate::shard::HASH_SIZE;
+use crate::types::{ceil_div, ObjectMeta};
+
+/// Position and size of one block inside a shard file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLayout {
+    pub index: usize,
+    /// Offset of the block hash (None for whole-file bitrot)
+    pub hash_offset: Option<u64>,
+    /// Offset of the shard data
+    pub data_offset: u64,
+    /// Shard data length (shorter for the last block)
+    pub data_size: u64,
+    /// Offset of the block within the part's object data
+    pub part_offset: i64,
+    /// Object bytes covered by the block (data_size * data_blocks, minus padding)
+    pub part_size: i64,
+}
+
+/// Geometry of one part's shard file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardLayout {
+    pub part_size: i64,
+    pub block_size: i64,
+    pub data_blocks: usize,
+    pub shard_size: i64,
+    /// Per-block hash size: HASH_SIZE for streaming bitrot, 0 otherwise
+    pub hash_size: i64,
+}
+
+impl ShardLayout {
+    /// Layout of the given part of an object
+    pub fn new(meta: &ObjectMeta, part_number: i32) -> Result<Self> {
+        let part = meta
+            .parts
+            .iter()
+            .find(|p| p.number == part_number)
+            .with_context(|| format!("part {} not found", part_number))?;
+        Ok(Self::for_part_size(meta, part.size))
+    }
+
+    /// Layout of a part of `part_size` bytes with the erasure parameters of `meta`
+    pub fn for_part_size(meta: &ObjectMeta, part_size: i64) -> Self {
+        ShardLayout {
+            part_size,
+            block_size: meta.block_size,
+            data_blocks: meta.data_blocks,
+            shard_size: meta.shard_size(),
+            hash_size: if meta.bitrot_algo.is_streaming() {
+                HASH_SIZE as i64
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Number of blocks in the part (0 for an empty part)
+    pub fn block_count(&self) -> usize {
+        ceil_div(self.part_size, self.block_size) as usize
+    }
+
+    /// Layout of block `index`, or None past the end of the part
+    pub fn block(&self, index: usize) -> Option<BlockLayout> {
+        if index >= self.block_count() {
+            return None;
+        }
+        let part_offset = index as i64 * self.block_size;
+        let part_size = std::cmp::min(self.block_size, self.part_size - part_offset);
+        let data_size = if part_size == self.block_size {
+            self.shard_size
+        } else {
+            ceil_div(part_size, self.data_blocks as i64)
+        };
+
+        let start = index as u64 * (self.hash_size + self.shard_size) as u64;
+        Some(BlockLayout {
+            index,
+            hash_offset: (self.hash_size > 0).then_some(start),
+            data_offset: start + self.hash_size as u64,
+            data_size: data_size as u64,
+            part_offset,
+            part_size,
+        })
+    }
+
+    /// All blocks in order
+    pub fn blocks(&self) -> impl Iterator<Item = BlockLayout> + '_ {
+        (0..self.block_count()).filter_map(|i| self.block(i))
+    }
+
+    /// Index of the block holding byte `offset` of the part
+    pub fn block_for_offset(&self, offset: i64) -> usize {
+        (offset / self.block_size) as usize
+    }
+
+    /// Expected size of the shard file
+    pub fn file_size(&self) -> u64 {
+        match self
+            .block_count()
+            .checked_sub(1)
+            .and_then(|i| self.block(i))
+        {
+            Some(last) => last.data_offset + last.data_size,
+            None => 0,
+        }
+    }
+}
+
+/// Outcome of comparing a shard file's length with the layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardSizeStatus {
+    Ok,
+    /// The shard file does not exist (or the disk is unavailable)
+    Missing,
+    /// Shorter than expected
+    Truncated,
+    /// Longer than expected
+    Oversized,
+    /// The length could not be determined
+    Unreadable(String),
+}
+
+/// Length check of one shard file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardSizeCheck {
+    pub disk_index: usize,
+    pub part_number: i32,
+    pub expected: u64,
+    pub actual: Option<u64>,
+    pub status: ShardSizeStatus,
+}
+
+/// Check every shard file of an object against its expected length, without
+/// reading any data.
+///
+/// `shard_len(disk_index, part_number)` returns the length of that disk's
+/// `part.N`, or None if it is missing. One entry is returned per disk in the
+/// distribution and per part. Inline objects (no data dir) have no part files
+/// and yield no entries.
+pub fn check_shard_sizes(
+    meta: &ObjectMeta,
+    mut shard_len: impl FnMut(usize, i32) -> Result<Option<u64>>,
+) -> Vec<ShardSizeCheck> {
+    if !meta.has_data_dir() {
+        return Vec::new();
+    }
+
+    let mut checks = Vec::with_capacity(meta.distribution.len() * meta.parts.len());
+    for part in &meta.parts {
+        let expected = ShardLayout::for_part_size(meta, part.size).file_size();
+        for disk_index in 0..meta.distribution.len() {
+            let (actual, status) = match shard_len(disk_index, part.number) {
+                Ok(None) => (None, ShardSizeStatus::Missing),
+                Ok(Some(len)) => {
+                    let status = match len.cmp(&expected) {
+                        Ordering::Equal => ShardSizeStatus::Ok,
+                        Ordering::Less => ShardSizeStatus::Truncated,
+                        Ordering::Greater => ShardSizeStatus::Oversized,
+                    };
+                    (Some(len), status)
+                }
+                Err(e) => (None, ShardSizeStatus::Unreadable(format!("{:#}", e))),
+            };
+            checks.push(ShardSizeCheck {
+                disk_index,
+                part_number: part.number,
+                expected,
+                actual,
+                status,
+            });
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BitrotAlgorithm, PartMeta};
+
+    fn meta(part_size: i64) -> ObjectMeta {
+        ObjectMeta {
+            data_blocks: 3,
+            parity_blocks: 2,
+            block_size: 1048576,
+            size: part_size,
+            parts: vec![PartMeta {
+                number: 1,
+                size: part_size,
+                actual_size: part_size,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_layout_multi_block_part() {
+        let layout = ShardLayout::new(&meta(2 * 1048576 + 100), 1).unwrap();
+        assert_eq!(layout.block_count(), 3);
+
+        let b1 = layout.block(1).unwrap();
+        assert_eq!(b1.hash_offset, Some(32 + 349526));
+        assert_eq!(b1.data_offset, 2 * 32 + 349526);
+        assert_eq!(b1.data_size, 349526);
+        assert_eq!(b1.part_offset, 1048576);
+        assert_eq!(b1.part_size, 1048576);
+
+        // Short last block: ceil(100 / 3) bytes per shard
+        let last = layout.block(2).unwrap();
+        assert_eq!(last.data_size, 34);
+        assert_eq!(last.part_size, 100);
+        assert!(layout.block(3).is_none());
+
+        assert_eq!(layout.file_size(), 2 * (32 + 349526) + 32 + 34);
+        assert_eq!(layout.blocks().count(), 3);
+        assert_eq!(layout.block_for_offset(1048576), 1);
+    }
+
+    #[test]
+    fn test_layout_matches_corpus_shard_size() {
+        // cicd-corpus testobj: part.1 files are 214872 bytes
+        let layout = ShardLayout::new(&meta(644520), 1).unwrap();
+        assert_eq!(layout.block_count(), 1);
+        assert_eq!(layout.file_size(), 214872);
+    }
+
+    #[test]
+    fn test_check_shard_sizes_flags_bad_disks() {
+        let mut m = meta(644520);
+        m.distribution = vec![3, 4, 5, 1, 2];
+        m.data_dir = crate::types::Uuid16([1; 16]);
+
+        let checks = check_shard_sizes(&m, |disk, _part| match disk {
+            0 => Ok(None),
+            1 => Ok(Some(214872 - 100)),
+            2 => Ok(Some(214872 + 1)),
+            3 => anyhow::bail!("permission denied"),
+            _ => Ok(Some(214872)),
+        });
+
+        let statuses: Vec<_> = checks.iter().map(|c| c.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ShardSizeStatus::Missing,
+                ShardSizeStatus::Truncated,
+                ShardSizeStatus::Oversized,
+                ShardSizeStatus::Unreadable("permission denied".into()),
+                ShardSizeStatus::Ok,
+            ]
+        );
+        assert!(checks.iter().all(|c| c.expected == 214872));
+        assert_eq!(checks[1].actual, Some(214772));
+
+        // Inline objects have no part files
+        m.data_dir = Default::default();
+        assert!(check_shard_sizes(&m, |_, _| Ok(None)).is_empty());
+    }
+
+    #[test]
+    fn test_layout_whole_file_bitrot_and_empty_part() {
+        let mut m = meta(1048576 + 3);
+        m.bitrot_algo = BitrotAlgorithm::Blake2b512;
+        let layout = ShardLayout::new(&m, 1).unwrap();
+        let last = layout.block(1).unwrap();
+        assert_eq!(last.hash_offset, None);
+        assert_eq!(last.data_offset, 349526);
+        assert_eq!(layout.file_size(), 349526 + 1);
+
+        let empty = ShardLayout::new(&meta(0), 1).unwrap();
+        assert_eq!(empty.block_count(), 0);
+        assert_eq!(empty.file_size(), 0);
+
+        assert!(ShardLayout::new(&meta(10), 2).is_err());
+    }
+}
@@ -3,8 +3,10 @@
 //! This library provides parsers and decoders for MinIO's internal data formats:
 //! - `xl.meta`: Object metadata (msgpack binary format)
 //! - `format.json`: Cluster topology and disk configuration
-//! - Shard files: Erasure-coded data with HighwayHash256 bitrot verification
+//! - Shard files: Erasure-coded data with bitrot verification (streaming HighwayHash256,
+//!   or whole-file SHA256 / BLAKE2b512 / HighwayHash256 for legacy objects)
 //! - Reed-Solomon erasure decoding
+//! - Bucket walks and point-in-time views over version histories
 //!
 //! # Example
 //!
@@ -23,17 +25,63 @@
 //! let data = decode_object(&reader, &meta, &[])?;
 //! ```
 
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+pub mod diagnostics;
+pub mod distribution;
+pub mod encode;
 pub mod erasure;
 pub mod format;
+pub mod heal;
+pub mod layout;
+pub mod parallel;
+pub mod reader;
+pub mod scrub;
 pub mod shard;
+pub mod sink;
 pub mod types;
+pub mod walk;
 pub mod xlmeta;
 
+#[cfg(test)]
+mod testutil;
+
 // Re-exports for convenient access
-pub use erasure::{decode_object, FsShardReader, ShardReader};
+#[cfg(feature = "tokio")]
+pub use asynchronous::{
+    decode_object_async, decode_object_range_async, decode_object_stream, AsyncShardReader,
+    TokioFsShardReader,
+};
+pub use diagnostics::{DecodeReport, ShardDiagnostic, ShardIssue};
+pub use distribution::{
+    distribution_matches, hash_key, hash_order, object_distribution, object_sets, place_object,
+    set_index, DistributionAlgo,
+};
+pub use encode::{encode_object, EncodedObject};
+#[cfg(feature = "mmap")]
+pub use erasure::MmapShardReader;
+pub use erasure::{
+    decode_object, decode_object_range, decode_object_to_writer, decode_object_version,
+    decode_object_with_report, FsShardReader, ShardReader, VersionedObject,
+};
 pub use format::{build_cluster_config, parse_format, DiskFormat, XLFormat};
-pub use shard::{read_shard_all_blocks, read_shard_block, shard_path, HASH_SIZE};
+pub use heal::heal_disk;
+pub use layout::{check_shard_sizes, BlockLayout, ShardLayout, ShardSizeCheck, ShardSizeStatus};
+pub use parallel::{decode_object_parallel, decode_object_parallel_to_writer};
+pub use reader::ObjectReader;
+pub use scrub::{scrub_object, ScrubReport, ScrubVerdict};
+pub use shard::{
+    read_shard_all_blocks, read_shard_block, read_shard_block_at, read_shard_block_from,
+    read_shard_block_with, scan_shard, shard_block_slice, shard_block_slice_with, shard_path,
+    verify_shard_file, ReadAt, ShardScanReport, ShardWriter, HASH_SIZE,
+};
+pub use sink::{FsShardSink, ShardFile, ShardSink};
 pub use types::{
-    ceil_div, ClusterConfig, DiskInfo, ObjectMeta, PartMeta, PoolConfig, Uuid16, VersionType,
+    ceil_div, BitrotAlgorithm, ClusterConfig, DiskInfo, ObjectMeta, PartMeta, PoolConfig, Uuid16,
+    VersionType,
+};
+pub use walk::{point_in_time, XlMetaWalk};
+pub use xlmeta::{
+    encode as encode_xlmeta, merge_version as merge_xlmeta_version, parse as parse_xlmeta,
+    parse_full as parse_xlmeta_full, XlMeta, XlVersion,
 };
-pub use xlmeta::parse as parse_xlmeta;
@@ -0,0 +1,278 @@
+//! Parallel erasure decoding
+//!
+//! One worker thread per disk reads shard blocks while the calling thread
+//! verifies and reconstructs, so Reed-Solomon work on block N overlaps the
+//! reads of block N+1. Output is written strictly in object order.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{Context, Result};
+
+use crate::erasure::{reconstruct_block, PartShards, ShardChecksums, ShardReader};
+use crate::layout::ShardLayout;
+use crate::types::ObjectMeta;
+
+/// A block of one part, identified by index into `meta.parts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BlockId {
+    part: usize,
+    block: usize,
+}
+
+/// Calling-thread handle of the worker reading one shard
+struct ShardWorker {
+    requests: mpsc::Sender<BlockId>,
+    responses: mpsc::Receiver<(BlockId, Option<Vec<u8>>)>,
+    // Blocks requested and not yet taken or forgotten
+    pending: HashSet<BlockId>,
+    // Responses that arrived while waiting for another block
+    received: HashMap<BlockId, Option<Vec<u8>>>,
+}
+
+impl ShardWorker {
+    /// Queue a read of `id` unless it is already queued
+    fn request(&mut self, id: BlockId) {
+        if self.pending.insert(id) {
+            // A dead worker shows up as a missing shard in `take`
+            let _ = self.requests.send(id);
+        }
+    }
+
+    /// Wait for block `id`, requesting it first if needed
+    fn take(&mut self, id: BlockId) -> Option<Vec<u8>> {
+        self.request(id);
+        loop {
+            if let Some(data) = self.received.remove(&id) {
+                self.pending.remove(&id);
+                return data;
+            }
+            match self.responses.recv() {
+                Ok((got, data)) => {
+                    // Responses to forgotten requests are dropped
+                    if self.pending.contains(&got) {
+                        self.received.insert(got, data);
+                    }
+                }
+                Err(_) => {
+                    self.pending.remove(&id);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Drop a request whose result is no longer needed
+    fn forget(&mut self, id: BlockId) {
+        self.pending.remove(&id);
+        self.received.remove(&id);
+    }
+}
+
+/// Decode a whole object into memory with one reader thread per disk.
+///
+/// Same result as `decode_object`; `reader` must be shareable between threads.
+pub fn decode_object_parallel(
+    reader: &(dyn ShardReader + Sync),
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+) -> Result<Vec<u8>> {
+    let mut result = Vec::with_capacity(meta.size as usize);
+    decode_object_parallel_to_writer(reader, meta, skip_disks, &mut result)?;
+    Ok(result)
+}
+
+/// Decode a whole object into `out` with one reader thread per disk,
+/// returning the bytes written.
+///
+/// Workers read data shards of the next block while the current one is
+/// reconstructed. Parity shards are read only for blocks that need them, and
+/// eagerly for the next block once a block needed reconstruction.
+pub fn decode_object_parallel_to_writer<W: Write + ?Sized>(
+    reader: &(dyn ShardReader + Sync),
+    meta: &ObjectMeta,
+    skip_disks: &[usize],
+    out: &mut W,
+) -> Result<u64> {
+    let data_blocks = meta.data_blocks;
+    let total_shards = meta.total_shards();
+    let shard_size = meta.shard_size();
+
+    // Every block of every part, in object order
+    let blocks: Vec<(BlockId, i64)> = meta
+        .parts
+        .iter()
+        .enumerate()
+        .flat_map(|(part, p)| {
+            let layout = ShardLayout::for_part_size(meta, p.size);
+            (0..layout.block_count())
+                .filter_map(move |block| layout.block(block))
+                .map(move |b| {
+                    (
+                        BlockId {
+                            part,
+                            block: b.index,
+                        },
+                        b.part_size,
+                    )
+                })
+        })
+        .collect();
+
+    // Shared by the workers so xl.meta copies are read once
+    let checksums = &ShardChecksums::read(reader, meta, skip_disks);
+
+    thread::scope(|scope| {
+        let mut workers: Vec<Option<ShardWorker>> = (0..total_shards)
+            .map(|shard_idx| {
+                let disk = meta
+                    .distribution
+                    .iter()
+                    .position(|&e| e as usize == shard_idx + 1)?;
+                if skip_disks.contains(&disk) {
+                    return None;
+                }
+                let (req_tx, req_rx) = mpsc::channel::<BlockId>();
+                let (resp_tx, resp_rx) = mpsc::channel();
+                scope.spawn(move || {
+                    let mut current: Option<(usize, PartShards)> = None;
+                    for id in req_rx {
+                        let part = &meta.parts[id.part];
+                        let shards = match &mut current {
+                            Some((p, shards)) if *p == id.part => shards,
+                            slot => {
+                                let layout = ShardLayout::for_part_size(meta, part.size);
+                                let shards = PartShards::new(
+                                    reader,
+                                    meta,
+                                    part.number,
+                                    skip_disks,
+                                    Some(layout),
+                                    checksums,
+                                );
+                                &mut slot.insert((id.part, shards)).1
+                            }
+                        };
+                        shards.load(shard_idx..shard_idx + 1, id.block);
+                        let data = shards
+                            .block(shard_idx, id.block, shard_size)
+                            .map(<[u8]>::to_vec);
+                        if resp_tx.send((id, data)).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Some(ShardWorker {
+                    requests: req_tx,
+                    responses: resp_rx,
+                    pending: HashSet::new(),
+                    received: HashMap::new(),
+                })
+            })
+            .collect();
+
+        let request = |workers: &mut [Option<ShardWorker>], id: BlockId, parity: bool| {
+            let shards = if parity { total_shards } else { data_blocks };
+            for worker in workers[..shards].iter_mut().flatten() {
+                worker.request(id);
+            }
+        };
+
+        let mut written = 0u64;
+        let mut remaining = meta.size.max(0) as u64;
+        let mut need_parity = false;
+        let mut block_data = Vec::new();
+        if let Some(&(first, _)) = blocks.first() {
+            request(&mut workers, first, need_parity);
+        }
+
+        for (i, &(id, block_len)) in blocks.iter().enumerate() {
+            // Queue the next block before working on this one
+            if let Some(&(next, _)) = blocks.get(i + 1) {
+                request(&mut workers, next, need_parity);
+            }
+
+            let mut take = |shard_idx: usize| {
+                workers[shard_idx]
+                    .as_mut()
+                    .and_then(|worker| worker.take(id))
+            };
+            let mut shards: Vec<Option<Vec<u8>>> = (0..data_blocks).map(&mut take).collect();
+
+            block_data.clear();
+            need_parity = shards.iter().any(Option::is_none);
+            if need_parity {
+                shards.extend((data_blocks..total_shards).map(&mut take));
+                let views: Vec<Option<&[u8]>> = shards.iter().map(|s| s.as_deref()).collect();
+                reconstruct_block(meta, &views, &mut block_data).with_context(|| {
+                    format!(
+                        "decode part {} block {}",
+                        meta.parts[id.part].number, id.block
+                    )
+                })?;
+            } else {
+                for shard in shards.iter().flatten() {
+                    block_data.extend_from_slice(shard);
+                }
+            }
+            for worker in workers.iter_mut().flatten() {
+                worker.forget(id);
+            }
+
+            // Trim padding and anything past the object size
+            let len = std::cmp::min(block_len as u64, remaining) as usize;
+            let data = block_data
+                .get(..len)
+                .context("decoded block shorter than expected")?;
+            out.write_all(data).context("write decoded block")?;
+            written += len as u64;
+            remaining -= len as u64;
+        }
+
+        out.flush().context("flush decoded object")?;
+        Ok(written)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erasure::decode_object;
+    use crate::shard::HASH_SIZE;
+    use crate::testutil::{corpus_meta, corpus_reader, multipart_object};
+
+    #[test]
+    fn test_decode_parallel_matches_sequential() {
+        let (data, meta, reader) = multipart_object();
+        for skip in [&[][..], &[0], &[1], &[2]] {
+            let got = decode_object_parallel(&reader, &meta, skip).unwrap();
+            assert!(got == data, "skip {:?}", skip);
+        }
+        assert!(decode_object_parallel(&reader, &meta, &[0, 1]).is_err());
+
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let full = decode_object(&reader, &meta, &[]).unwrap();
+        for skip in [&[][..], &[0, 1], &[2, 4]] {
+            let mut out = Vec::new();
+            let written = decode_object_parallel_to_writer(&reader, &meta, skip, &mut out).unwrap();
+            assert_eq!(written, full.len() as u64);
+            assert!(out == full, "skip {:?}", skip);
+        }
+    }
+
+    #[test]
+    fn test_decode_parallel_reconstructs_single_bad_block() {
+        let (data, meta, mut reader) = multipart_object();
+
+        // Corrupt block 2 of the shard on disk 0 in part 1 only
+        let block_stride = HASH_SIZE + meta.shard_size() as usize;
+        let shard = reader.parts[0][0].as_mut().unwrap();
+        shard[2 * block_stride + HASH_SIZE] ^= 0xFF;
+
+        let got = decode_object_parallel(&reader, &meta, &[]).unwrap();
+        assert!(got == data);
+    }
+}
@@ -0,0 +1,235 @@
+//! `Read + Seek` access to erasure-coded objects
+//!
+//! Decodes lazily one block at a time, so consumers that want a file-like
+//! handle (tar writers, hashers, columnar readers) never hold the whole object.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use anyhow::{Context, Result};
+
+use crate::erasure::{decode_block, PartShards, ShardChecksums, ShardReader};
+use crate::layout::ShardLayout;
+use crate::types::ObjectMeta;
+
+/// Seekable reader over an erasure-coded object.
+///
+/// The block holding the current position is decoded on first access and
+/// cached; seeking only moves the position, and the next read decodes the
+/// block it lands in. Shards of the current part are read by byte range.
+pub struct ObjectReader<'a> {
+    reader: &'a dyn ShardReader,
+    meta: &'a ObjectMeta,
+    skip_disks: &'a [usize],
+    pos: u64,
+    // Whole-file bitrot checksums, read with the first block
+    checksums: Option<ShardChecksums>,
+    // Index into meta.parts and shards of the part of the cached block
+    part: Option<(usize, PartShards<'a>)>,
+    // Decoded block covering object bytes block_start..block_start + block.len()
+    block_start: u64,
+    block: Vec<u8>,
+}
+
+impl<'a> ObjectReader<'a> {
+    /// Reader over `meta`'s object, never reading shards from `skip_disks`
+    pub fn new(reader: &'a dyn ShardReader, meta: &'a ObjectMeta, skip_disks: &'a [usize]) -> Self {
+        ObjectReader {
+            reader,
+            meta,
+            skip_disks,
+            pos: 0,
+            checksums: None,
+            part: None,
+            block_start: 0,
+            block: Vec::new(),
+        }
+    }
+
+    /// Object size in bytes
+    pub fn len(&self) -> u64 {
+        self.meta.size as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meta.size == 0
+    }
+
+    /// Decode the block holding object byte `pos` unless it is already cached
+    fn load_block(&mut self, pos: u64) -> Result<()> {
+        let cached = self.block_start..self.block_start + self.block.len() as u64;
+        if cached.contains(&pos) {
+            return Ok(());
+        }
+
+        // Find the part holding pos
+        let mut part_start = 0u64;
+        let mut part_idx = None;
+        for (i, part) in self.meta.parts.iter().enumerate() {
+            if pos < part_start + part.size as u64 {
+                part_idx = Some(i);
+                break;
+            }
+            part_start += part.size as u64;
+        }
+        let part_idx = part_idx.with_context(|| format!("offset {} beyond last part", pos))?;
+        let part = &self.meta.parts[part_idx];
+
+        let layout = ShardLayout::for_part_size(self.meta, part.size);
+        let (reader, meta, skip_disks) = (self.reader, self.meta, self.skip_disks);
+        let checksums = self
+            .checksums
+            .get_or_insert_with(|| ShardChecksums::read(reader, meta, skip_disks));
+        let shards = match &mut self.part {
+            Some((i, shards)) if *i == part_idx => shards,
+            slot => {
+                let shards = PartShards::new(
+                    reader,
+                    meta,
+                    part.number,
+                    skip_disks,
+                    Some(layout),
+                    checksums,
+                );
+                &mut slot.insert((part_idx, shards)).1
+            }
+        };
+
+        let index = layout.block_for_offset((pos - part_start) as i64);
+        let block = layout.block(index).context("block outside part")?;
+
+        self.block.clear();
+        let decoded = decode_block(shards, index, self.meta.shard_size(), &mut self.block, None);
+        if let Err(e) = decoded {
+            // Never leave a partial block cached
+            self.block.clear();
+            return Err(e.context(format!("decode part {} block {}", part.number, index)));
+        }
+        self.block.truncate(block.part_size as usize);
+        self.block_start = part_start + block.part_offset as u64;
+        Ok(())
+    }
+}
+
+impl Read for ObjectReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len() {
+            return Ok(0);
+        }
+        self.load_block(self.pos).map_err(io::Error::other)?;
+
+        let start = (self.pos - self.block_start) as usize;
+        let available = std::cmp::min(self.block.len(), (self.len() - self.block_start) as usize);
+        let n = std::cmp::min(buf.len(), available - start);
+        buf[..n].copy_from_slice(&self.block[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for ObjectReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len().checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        match new_pos {
+            Some(p) => {
+                self.pos = p;
+                Ok(p)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::erasure::decode_object;
+    use crate::testutil::{corpus_meta, corpus_reader, multipart_object};
+
+    #[test]
+    fn test_object_reader_reads_whole_object() {
+        let (data, meta, shards) = multipart_object();
+        let mut out = Vec::new();
+        ObjectReader::new(&shards, &meta, &[1])
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, data);
+
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let mut out = Vec::new();
+        ObjectReader::new(&reader, &meta, &[0, 4])
+            .read_to_end(&mut out)
+            .unwrap();
+        assert!(out == decode_object(&reader, &meta, &[]).unwrap());
+    }
+
+    #[test]
+    fn test_object_reader_seek() {
+        let (data, meta, shards) = multipart_object();
+        let mut reader = ObjectReader::new(&shards, &meta, &[]);
+        let mut buf = [0u8; 100];
+
+        // Across the part boundary at 300
+        assert_eq!(reader.seek(SeekFrom::Start(250)).unwrap(), 250);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf[..], &data[250..350]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-200)).unwrap(), 150);
+        reader.read_exact(&mut buf[..10]).unwrap();
+        assert_eq!(&buf[..10], &data[150..160]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-5)).unwrap(), 695);
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &data[695..]);
+
+        // Past the end reads nothing; before the start is an error
+        reader.seek(SeekFrom::Start(1000)).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(reader.seek(SeekFrom::Current(-2000)).is_err());
+    }
+
+    #[test]
+    fn test_object_reader_caches_current_block() {
+        let (data, meta, shards) = multipart_object();
+        let mut reader = ObjectReader::new(&shards, &meta, &[]);
+        let mut byte = [0u8; 1];
+
+        // 64-byte blocks of a 2+1 object: each block is two range reads
+        for expected in &data[..64] {
+            reader.read_exact(&mut byte).unwrap();
+            assert_eq!(byte[0], *expected);
+        }
+        assert_eq!(shards.range_reads.load(Ordering::Relaxed), 2);
+
+        // Seeking within the cached block reads nothing new
+        reader.seek(SeekFrom::Start(10)).unwrap();
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(shards.range_reads.load(Ordering::Relaxed), 2);
+
+        reader.seek(SeekFrom::Start(64)).unwrap();
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], data[64]);
+        assert_eq!(shards.range_reads.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_object_reader_reports_unrecoverable_block() {
+        let reader = corpus_reader();
+        let meta = corpus_meta();
+        let mut out = Vec::new();
+        let err = ObjectReader::new(&reader, &meta, &[0, 1, 2])
+            .read_to_end(&mut out)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+}
@@ -3,9 +3,23 @@
 //! Port of erasure/shard.go. Reads raw shard bytes and extracts blocks.
 //!
 //! Shard file layout (per block): `[32-byte HighwayHash256][up to shard_size bytes]`
+//!
+//! Objects written with a whole-file bitrot algorithm (SHA256, BLAKE2b512,
+//! non-streaming HighwayHash256) have no interleaved hashes: the shard file is
+//! the plain concatenation of blocks, and its checksum is stored in xl.meta.
+//!
+//! Block N starts at offset `N * (HASH_SIZE + shard_size)`, so single blocks can
+//! be fetched from a `Read + Seek` source or by positional reads without loading
+//! the whole shard file.
+
+use std::io::{Read, Seek, SeekFrom, Write};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use blake2::Blake2b512;
 use highway::{HighwayHash, HighwayHasher, Key};
+use sha2::{Digest, Sha256};
+
+use crate::types::BitrotAlgorithm;
 
 /// Size of HighwayHash256 checksum in bytes
 pub const HASH_SIZE: usize = 32;
@@ -47,24 +61,24 @@ pub fn read_shard_block(
     shard_size: i64,
     verify_bitrot: bool,
 ) -> Result<Option<Vec<u8>>> {
-    let file_size = shard_data.len() as i64;
-    if file_size <= HASH_SIZE as i64 {
-        bail!("shard too small: {} bytes", file_size);
-    }
-
-    // Calculate offset for this block
-    let block_offset = block_index as i64 * (HASH_SIZE as i64 + shard_size);
+    Ok(shard_block_slice(shard_data, block_index, shard_size, verify_bitrot)?.map(<[u8]>::to_vec))
+}
 
-    // For the last block, data might be smaller
-    let remaining = file_size - block_offset;
-    if remaining <= HASH_SIZE as i64 {
+/// Like `read_shard_block`, but returns a view into `shard_data` instead of a copy.
+pub fn shard_block_slice(
+    shard_data: &[u8],
+    block_index: usize,
+    shard_size: i64,
+    verify_bitrot: bool,
+) -> Result<Option<&[u8]>> {
+    let Some((offset, data_size)) = block_extent(shard_data.len() as u64, block_index, shard_size)?
+    else {
         return Ok(None); // No data for this block
-    }
+    };
 
-    let data_size = std::cmp::min(remaining - HASH_SIZE as i64, shard_size);
-    let hash_start = block_offset as usize;
+    let hash_start = offset as usize;
     let data_start = hash_start + HASH_SIZE;
-    let data_end = data_start + data_size as usize;
+    let data_end = data_start + data_size;
 
     let hash_buf = &shard_data[hash_start..data_start];
     let data = &shard_data[data_start..data_end];
@@ -73,7 +87,216 @@ pub fn read_shard_block(
         verify_highway_hash(data, hash_buf, block_index)?;
     }
 
-    Ok(Some(data.to_vec()))
+    Ok(Some(data))
+}
+
+/// Read a single block from shard data written with the given bitrot algorithm.
+///
+/// For the streaming algorithm this is `read_shard_block`. For whole-file
+/// algorithms block N is at offset `N * shard_size` and is returned
+/// unverified; check the whole file with `verify_shard_file` first.
+pub fn read_shard_block_with(
+    shard_data: &[u8],
+    block_index: usize,
+    shard_size: i64,
+    algo: BitrotAlgorithm,
+    verify_bitrot: bool,
+) -> Result<Option<Vec<u8>>> {
+    Ok(
+        shard_block_slice_with(shard_data, block_index, shard_size, algo, verify_bitrot)?
+            .map(<[u8]>::to_vec),
+    )
+}
+
+/// Like `read_shard_block_with`, but returns a view into `shard_data` instead of a copy.
+pub fn shard_block_slice_with(
+    shard_data: &[u8],
+    block_index: usize,
+    shard_size: i64,
+    algo: BitrotAlgorithm,
+    verify_bitrot: bool,
+) -> Result<Option<&[u8]>> {
+    if algo.is_streaming() {
+        return shard_block_slice(shard_data, block_index, shard_size, verify_bitrot);
+    }
+
+    if shard_data.is_empty() {
+        bail!("shard is empty");
+    }
+    let start = block_index as u64 * shard_size as u64;
+    if start >= shard_data.len() as u64 {
+        return Ok(None);
+    }
+    let start = start as usize;
+    let end = std::cmp::min(start + shard_size as usize, shard_data.len());
+    Ok(Some(&shard_data[start..end]))
+}
+
+/// Verify a whole shard file against the checksum stored in xl.meta.
+pub fn verify_shard_file(shard_data: &[u8], algo: BitrotAlgorithm, expected: &[u8]) -> Result<()> {
+    if algo.is_streaming() {
+        bail!("{:?} checksums are stored per block, not per file", algo);
+    }
+    if bitrot_hash(algo, shard_data) != expected {
+        bail!("bitrot detected in shard file ({:?})", algo);
+    }
+    Ok(())
+}
+
+/// Compute the bitrot digest of `data` with the given algorithm.
+///
+/// For HighwayHash256S this is the per-block hash stored before each block.
+pub fn bitrot_hash(algo: BitrotAlgorithm, data: &[u8]) -> Vec<u8> {
+    match algo {
+        BitrotAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+        BitrotAlgorithm::Blake2b512 => Blake2b512::digest(data).to_vec(),
+        BitrotAlgorithm::HighwayHash256 | BitrotAlgorithm::HighwayHash256S => {
+            highway_hash(data).to_vec()
+        }
+    }
+}
+
+/// HighwayHash256 with MinIO's key, as little-endian bytes
+fn highway_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hasher = HighwayHasher::new(highway_key());
+    hasher.append(data);
+    let computed = hasher.finalize256();
+
+    // finalize256() returns [u64; 4] — convert to bytes
+    let mut bytes = [0u8; HASH_SIZE];
+    for (i, &val) in computed.iter().enumerate() {
+        bytes[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
+    }
+    bytes
+}
+
+/// Source supporting positional reads (`pread`-style), e.g. a file shared
+/// between threads without seeking.
+pub trait ReadAt {
+    /// Read exactly `buf.len()` bytes starting at `offset`.
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<()>;
+}
+
+impl ReadAt for [u8] {
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        match start
+            .checked_add(buf.len())
+            .and_then(|end| self.get(start..end))
+        {
+            Some(src) => {
+                buf.copy_from_slice(src);
+                Ok(())
+            }
+            None => Err(std::io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+impl ReadAt for std::fs::File {
+    #[cfg(unix)]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+        use std::os::windows::fs::FileExt;
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Locate a block inside a shard file of `file_size` bytes.
+///
+/// Returns `(hash offset, data length)`, or None if the file has no data for
+/// this block.
+fn block_extent(
+    file_size: u64,
+    block_index: usize,
+    shard_size: i64,
+) -> Result<Option<(u64, usize)>> {
+    if file_size <= HASH_SIZE as u64 {
+        bail!("shard too small: {} bytes", file_size);
+    }
+
+    let block_offset = block_index as u64 * (HASH_SIZE as u64 + shard_size as u64);
+    if block_offset >= file_size || file_size - block_offset <= HASH_SIZE as u64 {
+        return Ok(None);
+    }
+
+    let remaining = file_size - block_offset - HASH_SIZE as u64;
+    let data_size = std::cmp::min(remaining, shard_size as u64) as usize;
+    Ok(Some((block_offset, data_size)))
+}
+
+/// Read a single block from a shard file through `Read + Seek`.
+///
+/// Only the block's hash and data are read; the file size is taken from
+/// seeking to the end. Same result as `read_shard_block` on the full contents.
+pub fn read_shard_block_from<R: Read + Seek + ?Sized>(
+    reader: &mut R,
+    block_index: usize,
+    shard_size: i64,
+    verify_bitrot: bool,
+) -> Result<Option<Vec<u8>>> {
+    let file_size = reader.seek(SeekFrom::End(0)).context("seek shard end")?;
+    let Some((offset, data_size)) = block_extent(file_size, block_index, shard_size)? else {
+        return Ok(None);
+    };
+
+    let mut buf = vec![0u8; HASH_SIZE + data_size];
+    reader
+        .seek(SeekFrom::Start(offset))
+        .context("seek shard block")?;
+    reader
+        .read_exact(&mut buf)
+        .with_context(|| format!("read shard block {}", block_index))?;
+
+    finish_block(buf, block_index, verify_bitrot)
+}
+
+/// Read a single block from a shard file of `file_size` bytes using positional reads.
+pub fn read_shard_block_at<R: ReadAt + ?Sized>(
+    reader: &R,
+    file_size: u64,
+    block_index: usize,
+    shard_size: i64,
+    verify_bitrot: bool,
+) -> Result<Option<Vec<u8>>> {
+    let Some((offset, data_size)) = block_extent(file_size, block_index, shard_size)? else {
+        return Ok(None);
+    };
+
+    let mut buf = vec![0u8; HASH_SIZE + data_size];
+    reader
+        .read_exact_at(&mut buf, offset)
+        .with_context(|| format!("read shard block {}", block_index))?;
+
+    finish_block(buf, block_index, verify_bitrot)
+}
+
+/// Verify a `[hash][data]` buffer and strip the hash
+fn finish_block(
+    mut buf: Vec<u8>,
+    block_index: usize,
+    verify_bitrot: bool,
+) -> Result<Option<Vec<u8>>> {
+    if verify_bitrot {
+        verify_highway_hash(&buf[HASH_SIZE..], &buf[..HASH_SIZE], block_index)?;
+    }
+    buf.drain(..HASH_SIZE);
+    Ok(Some(buf))
 }
 
 /// Read all blocks from shard data, returning concatenated data (without hashes).
@@ -117,19 +340,139 @@ pub fn read_shard_all_blocks(
     Ok(result)
 }
 
-/// Verify HighwayHash256 of data against expected hash
-fn verify_highway_hash(data: &[u8], expected_hash: &[u8], block_index: usize) -> Result<()> {
-    let mut hasher = HighwayHasher::new(highway_key());
-    hasher.append(data);
-    let computed = hasher.finalize256();
+/// Streaming writer for shard files in the HighwayHash256S layout.
+///
+/// Each call to `write_block` emits `[32-byte HighwayHash256][data]`, the
+/// layout `read_shard_block` expects. Every block must be exactly
+/// `shard_size` bytes except the last one, which may be shorter.
+pub struct ShardWriter<W: Write> {
+    inner: W,
+    shard_size: usize,
+    written: u64,
+    finished_short: bool,
+}
 
-    // finalize256() returns [u64; 4] — convert to bytes for comparison
-    let mut computed_bytes = [0u8; 32];
-    for (i, &val) in computed.iter().enumerate() {
-        computed_bytes[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
+impl<W: Write> ShardWriter<W> {
+    pub fn new(inner: W, shard_size: i64) -> Self {
+        ShardWriter {
+            inner,
+            shard_size: shard_size as usize,
+            written: 0,
+            finished_short: false,
+        }
+    }
+
+    /// Write one block of shard data, prefixed with its hash.
+    pub fn write_block(&mut self, data: &[u8]) -> Result<()> {
+        if self.finished_short {
+            bail!("block written after a short (last) block");
+        }
+        if data.is_empty() || data.len() > self.shard_size {
+            bail!(
+                "invalid block length {} for shard size {}",
+                data.len(),
+                self.shard_size
+            );
+        }
+        self.finished_short = data.len() < self.shard_size;
+
+        self.inner
+            .write_all(&highway_hash(data))
+            .context("write block hash")?;
+        self.inner.write_all(data).context("write block data")?;
+        self.written += (HASH_SIZE + data.len()) as u64;
+        Ok(())
+    }
+
+    /// Bytes written so far (hashes included)
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+
+    /// Flush and return the inner writer together with the final file size.
+    pub fn finish(mut self) -> Result<(W, u64)> {
+        self.inner.flush().context("flush shard")?;
+        Ok((self.inner, self.written))
+    }
+}
+
+/// Result of scanning every block of a shard file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardScanReport {
+    pub file_size: u64,
+    /// Number of blocks holding data (including a short last block)
+    pub total_blocks: usize,
+    /// Indices of blocks whose hash does not match their data
+    pub corrupted_blocks: Vec<usize>,
+    /// The file ends in a hash without data, or is shorter than expected
+    pub truncated_tail: bool,
+    /// Expected file size, if it was provided (see `ObjectMeta::shard_file_size`)
+    pub expected_size: Option<u64>,
+}
+
+impl ShardScanReport {
+    /// Whether the file size equals the expected size (None if unknown)
+    pub fn size_matches(&self) -> Option<bool> {
+        self.expected_size.map(|e| e == self.file_size)
+    }
+
+    /// No corrupted blocks, no truncation, and size as expected (if known)
+    pub fn is_healthy(&self) -> bool {
+        self.corrupted_blocks.is_empty()
+            && !self.truncated_tail
+            && self.size_matches().unwrap_or(true)
+    }
+}
+
+/// Scan a HighwayHash256S shard file, verifying every block instead of
+/// stopping at the first mismatch.
+pub fn scan_shard(
+    shard_data: &[u8],
+    shard_size: i64,
+    expected_size: Option<u64>,
+) -> ShardScanReport {
+    let file_size = shard_data.len() as u64;
+    let mut report = ShardScanReport {
+        file_size,
+        expected_size,
+        ..Default::default()
+    };
+
+    let mut block = 0;
+    loop {
+        let extent = match block_extent(file_size, block, shard_size) {
+            Ok(Some(extent)) => extent,
+            _ => break,
+        };
+        let (offset, data_size) = extent;
+        let hash_start = offset as usize;
+        let data_start = hash_start + HASH_SIZE;
+        let data = &shard_data[data_start..data_start + data_size];
+        if highway_hash(data) != shard_data[hash_start..data_start] {
+            report.corrupted_blocks.push(block);
+        }
+        report.total_blocks += 1;
+        block += 1;
     }
 
-    if computed_bytes != expected_hash {
+    // Bytes left after the last block that cannot form a block
+    let consumed = if report.total_blocks == 0 {
+        0
+    } else {
+        let (offset, data_size) = block_extent(file_size, report.total_blocks - 1, shard_size)
+            .ok()
+            .flatten()
+            .unwrap_or((0, 0));
+        offset + (HASH_SIZE + data_size) as u64
+    };
+    report.truncated_tail = consumed < file_size || expected_size.is_some_and(|e| file_size < e);
+
+    report
+}
+
+/// Verify HighwayHash256 of data against expected hash
+fn verify_highway_hash(data: &[u8], expected_hash: &[u8], block_index: usize) -> Result<()> {
+    if highway_hash(data) != expected_hash {
         bail!("bitrot detected in block {}", block_index);
     }
 
@@ -215,6 +558,201 @@ mod tests {
         assert!(result.is_none());
     }
 
+    /// Build a shard file from the given per-block data, with valid hashes
+    fn make_shard(blocks: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for block in blocks {
+            let mut hasher = HighwayHasher::new(highway_key());
+            hasher.append(block);
+            for val in hasher.finalize256() {
+                out.extend_from_slice(&val.to_le_bytes());
+            }
+            out.extend_from_slice(block);
+        }
+        out
+    }
+
+    #[test]
+    fn test_read_shard_block_from_matches_in_memory() {
+        let shard_size = 16i64;
+        let data = make_shard(&[&[1u8; 16], &[2u8; 16], &[3u8; 5]]);
+        let mut cursor = std::io::Cursor::new(data.clone());
+
+        for block in 0..4 {
+            let expected = read_shard_block(&data, block, shard_size, true).unwrap();
+            let streamed = read_shard_block_from(&mut cursor, block, shard_size, true).unwrap();
+            let positional =
+                read_shard_block_at(&data[..], data.len() as u64, block, shard_size, true).unwrap();
+            assert_eq!(streamed, expected, "block {}", block);
+            assert_eq!(positional, expected, "block {}", block);
+        }
+        assert_eq!(
+            read_shard_block_from(&mut cursor, 2, shard_size, true).unwrap(),
+            Some(vec![3u8; 5])
+        );
+        assert!(read_shard_block_from(&mut cursor, 3, shard_size, true)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_read_shard_block_at_file_detects_bitrot() {
+        let shard_size = 16i64;
+        let mut data = make_shard(&[&[1u8; 16], &[2u8; 16]]);
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+
+        let mut file = tempfile::tempfile().unwrap();
+        std::io::Write::write_all(&mut file, &data).unwrap();
+        let size = data.len() as u64;
+
+        assert_eq!(
+            read_shard_block_at(&file, size, 0, shard_size, true).unwrap(),
+            Some(vec![1u8; 16])
+        );
+        let err = read_shard_block_at(&file, size, 1, shard_size, true).unwrap_err();
+        assert!(err.to_string().contains("bitrot"));
+
+        // A file shorter than claimed fails instead of returning short data
+        assert!(read_shard_block_at(&file, size + 40, 2, shard_size, false).is_err());
+    }
+
+    #[test]
+    fn test_whole_file_bitrot_algorithms() {
+        let data: Vec<u8> = (0..100u8).collect();
+
+        for algo in [
+            BitrotAlgorithm::Sha256,
+            BitrotAlgorithm::HighwayHash256,
+            BitrotAlgorithm::Blake2b512,
+        ] {
+            let hash = bitrot_hash(algo, &data);
+            assert_eq!(hash.len(), algo.digest_size());
+            verify_shard_file(&data, algo, &hash).unwrap();
+
+            let mut corrupt = data.clone();
+            corrupt[50] ^= 1;
+            let err = verify_shard_file(&corrupt, algo, &hash).unwrap_err();
+            assert!(err.to_string().contains("bitrot"));
+        }
+
+        // Known SHA-256 of the empty input
+        assert_eq!(
+            bitrot_hash(BitrotAlgorithm::Sha256, b"")[..4],
+            [0xe3, 0xb0, 0xc4, 0x42]
+        );
+        assert!(verify_shard_file(&data, BitrotAlgorithm::HighwayHash256S, &[]).is_err());
+    }
+
+    #[test]
+    fn test_read_shard_block_with_whole_file_layout() {
+        let data: Vec<u8> = (0..40u8).collect();
+        let algo = BitrotAlgorithm::Sha256;
+
+        let block = read_shard_block_with(&data, 1, 16, algo, true).unwrap();
+        assert_eq!(block, Some((16..32u8).collect()));
+        let last = read_shard_block_with(&data, 2, 16, algo, true).unwrap();
+        assert_eq!(last, Some((32..40u8).collect()));
+        assert!(read_shard_block_with(&data, 3, 16, algo, true)
+            .unwrap()
+            .is_none());
+
+        // Streaming layout goes through the interleaved hash path
+        let streaming = make_shard(&[&[7u8; 16]]);
+        let block =
+            read_shard_block_with(&streaming, 0, 16, BitrotAlgorithm::HighwayHash256S, true)
+                .unwrap();
+        assert_eq!(block, Some(vec![7u8; 16]));
+    }
+
+    #[test]
+    fn test_shard_writer_roundtrip() {
+        let shard_size = 16i64;
+        let mut writer = ShardWriter::new(Vec::new(), shard_size);
+        writer.write_block(&[1u8; 16]).unwrap();
+        writer.write_block(&[2u8; 16]).unwrap();
+        writer.write_block(&[3u8; 5]).unwrap();
+        assert!(writer.write_block(&[4u8; 16]).is_err());
+
+        let (data, size) = writer.finish().unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(size, 3 * HASH_SIZE as u64 + 37);
+        assert_eq!(data, make_shard(&[&[1u8; 16], &[2u8; 16], &[3u8; 5]]));
+
+        assert_eq!(
+            read_shard_all_blocks(&data, shard_size, true).unwrap(),
+            [vec![1u8; 16], vec![2u8; 16], vec![3u8; 5]].concat()
+        );
+    }
+
+    #[test]
+    fn test_shard_writer_rejects_oversized_block() {
+        let mut writer = ShardWriter::new(Vec::new(), 4);
+        assert!(writer.write_block(&[0u8; 5]).is_err());
+        assert!(writer.write_block(&[]).is_err());
+        assert_eq!(writer.bytes_written(), 0);
+    }
+
+    #[test]
+    fn test_scan_shard_reports_all_corrupted_blocks() {
+        let shard_size = 16i64;
+        let mut data = make_shard(&[&[1u8; 16], &[2u8; 16], &[3u8; 16], &[4u8; 5]]);
+        let expected = data.len() as u64;
+
+        let report = scan_shard(&data, shard_size, Some(expected));
+        assert_eq!(report.total_blocks, 4);
+        assert!(report.is_healthy());
+
+        // Corrupt blocks 0 and 2
+        data[HASH_SIZE] ^= 1;
+        data[2 * (HASH_SIZE + 16) + HASH_SIZE + 3] ^= 1;
+        let report = scan_shard(&data, shard_size, Some(expected));
+        assert_eq!(report.corrupted_blocks, vec![0, 2]);
+        assert!(!report.truncated_tail);
+        assert_eq!(report.size_matches(), Some(true));
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn test_scan_shard_detects_truncation() {
+        let shard_size = 16i64;
+        let data = make_shard(&[&[1u8; 16], &[2u8; 16]]);
+        let expected = data.len() as u64;
+
+        // Cut inside the hash of block 1: block 0 fine, dangling bytes
+        let report = scan_shard(&data[..HASH_SIZE + 16 + 10], shard_size, Some(expected));
+        assert_eq!(report.total_blocks, 1);
+        assert!(report.corrupted_blocks.is_empty());
+        assert!(report.truncated_tail);
+        assert_eq!(report.size_matches(), Some(false));
+
+        // Cut inside the data of block 1: short block fails its hash
+        let report = scan_shard(&data[..data.len() - 4], shard_size, Some(expected));
+        assert_eq!(report.total_blocks, 2);
+        assert_eq!(report.corrupted_blocks, vec![1]);
+        assert!(report.truncated_tail);
+
+        // Without an expected size, a clean short file looks fine
+        let report = scan_shard(&data[..HASH_SIZE + 16], shard_size, None);
+        assert!(report.is_healthy());
+        assert_eq!(report.size_matches(), None);
+    }
+
+    #[test]
+    fn test_shard_block_slice_borrows_shard_data() {
+        let data = make_shard(&[&[1u8; 16], &[2u8; 4]]);
+        let block = shard_block_slice(&data, 1, 16, true).unwrap().unwrap();
+        assert_eq!(block, &[2u8; 4]);
+        // The view points into the original buffer
+        assert_eq!(block.as_ptr(), data[2 * HASH_SIZE + 16..].as_ptr());
+
+        let plain: Vec<u8> = (0..20u8).collect();
+        let block = shard_block_slice_with(&plain, 1, 16, BitrotAlgorithm::Sha256, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.as_ptr(), plain[16..].as_ptr());
+    }
+
     #[test]
     fn test_shard_too_small_error() {
         let data = vec![0u8; HASH_SIZE - 1];
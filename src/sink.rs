@@ -0,0 +1,210 @@
+//! Destinations for shard files and xl.meta copies
+//!
+//! The write-side counterpart of `ShardReader`, used by heal to store
+//! regenerated files on a disk.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{bail, Context, Result};
+
+use crate::shard::shard_path;
+
+/// A shard file being written. It only replaces `part.N` once committed;
+/// dropping it uncommitted discards what was written.
+pub trait ShardFile: Write {
+    /// Make the written data the shard's `part.N` file
+    fn commit(self: Box<Self>) -> Result<()>;
+}
+
+/// Stores shard files and xl.meta copies by disk
+pub trait ShardSink {
+    /// Open the `part.N` file of a shard for streaming writes. Any existing
+    /// file is left untouched until the returned file is committed.
+    fn create_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Box<dyn ShardFile + '_>>;
+
+    /// Write the whole `part.N` file of a shard
+    fn write_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut file = self.create_shard(disk_index, bucket, key, data_dir, part_number)?;
+        file.write_all(data).context("write shard")?;
+        file.commit()
+    }
+
+    /// Write the xl.meta copy of a disk
+    fn write_xlmeta(&self, disk_index: usize, bucket: &str, key: &str, data: &[u8]) -> Result<()>;
+}
+
+/// Writes into MinIO disk directories, laid out like `FsShardReader` reads them
+pub struct FsShardSink {
+    pub disk_paths: Vec<String>,
+}
+
+impl FsShardSink {
+    /// Path of `rel` on a disk, with its parent directories created
+    fn prepare(&self, disk_index: usize, rel: &str) -> Result<String> {
+        let Some(base) = self.disk_paths.get(disk_index).filter(|p| !p.is_empty()) else {
+            bail!("no path configured for disk {}", disk_index);
+        };
+        let path = format!("{}/{}", base, rel);
+        if let Some(dir) = Path::new(&path).parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        Ok(path)
+    }
+}
+
+impl ShardSink for FsShardSink {
+    fn create_shard(
+        &self,
+        disk_index: usize,
+        bucket: &str,
+        key: &str,
+        data_dir: &str,
+        part_number: i32,
+    ) -> Result<Box<dyn ShardFile + '_>> {
+        let path = self.prepare(disk_index, &shard_path(bucket, key, data_dir, part_number))?;
+        Ok(Box::new(TempFile::create(path.into())?))
+    }
+
+    fn write_xlmeta(&self, disk_index: usize, bucket: &str, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.prepare(disk_index, &format!("{}/{}/xl.meta", bucket, key))?;
+        let mut file = TempFile::create(path.into())?;
+        file.write_all(data).context("write xl.meta")?;
+        Box::new(file).commit()
+    }
+}
+
+/// File written next to `path` and renamed over it once synced, so readers
+/// never see a partial file. Removed if dropped uncommitted.
+struct TempFile {
+    file: BufWriter<File>,
+    path: PathBuf,
+    // None once renamed into place
+    tmp: Option<PathBuf>,
+}
+
+impl TempFile {
+    fn create(path: PathBuf) -> Result<Self> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp = PathBuf::from(tmp);
+        let file = File::create(&tmp).with_context(|| format!("create {}", tmp.display()))?;
+        Ok(TempFile {
+            file: BufWriter::new(file),
+            path,
+            tmp: Some(tmp),
+        })
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl ShardFile for TempFile {
+    fn commit(mut self: Box<Self>) -> Result<()> {
+        let tmp = self.tmp.take().context("file already committed")?;
+        let written = self
+            .file
+            .flush()
+            .and_then(|()| self.file.get_ref().sync_all())
+            .with_context(|| format!("sync {}", tmp.display()))
+            .and_then(|()| {
+                std::fs::rename(&tmp, &self.path)
+                    .with_context(|| format!("rename {} to {}", tmp.display(), self.path.display()))
+            });
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        written
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(tmp) = self.tmp.take() {
+            let _ = std::fs::remove_file(tmp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_shard_replaced_only_on_commit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sink = FsShardSink {
+            disk_paths: vec![tmp.path().display().to_string()],
+        };
+        sink.write_shard(0, "bucket", "key", "dir", 1, b"old")
+            .unwrap();
+        let part_dir = tmp.path().join("bucket/key/dir");
+
+        // Uncommitted writes never touch part.1 and leave nothing behind
+        let mut file = sink.create_shard(0, "bucket", "key", "dir", 1).unwrap();
+        file.write_all(b"new data").unwrap();
+        file.flush().unwrap();
+        assert_eq!(std::fs::read(part_dir.join("part.1")).unwrap(), b"old");
+        drop(file);
+        assert_eq!(std::fs::read(part_dir.join("part.1")).unwrap(), b"old");
+        assert_eq!(dir_entries(&part_dir), vec!["part.1"]);
+
+        let mut file = sink.create_shard(0, "bucket", "key", "dir", 1).unwrap();
+        file.write_all(b"new data").unwrap();
+        file.commit().unwrap();
+        assert_eq!(std::fs::read(part_dir.join("part.1")).unwrap(), b"new data");
+        assert_eq!(dir_entries(&part_dir), vec!["part.1"]);
+    }
+
+    #[test]
+    fn test_write_xlmeta_replaces_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sink = FsShardSink {
+            disk_paths: vec![tmp.path().display().to_string()],
+        };
+        sink.write_xlmeta(0, "bucket", "key", b"first").unwrap();
+        sink.write_xlmeta(0, "bucket", "key", b"second").unwrap();
+        let dir = tmp.path().join("bucket/key");
+        assert_eq!(std::fs::read(dir.join("xl.meta")).unwrap(), b"second");
+        assert_eq!(dir_entries(&dir), vec!["xl.meta"]);
+    }
+}
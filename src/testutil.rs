@@ -0,0 +1,130 @@
+//! Fixtures shared by the decoder tests
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use reed_solomon_erasure::galois_8::ReedSolomon;
+
+use crate::erasure::{FsShardReader, ShardReader};
+use crate::shard::ShardWriter;
+use crate::types::{ObjectMeta, PartMeta};
+
+/// Reader over the five disks of testdata/cicd-corpus
+pub(crate) fn corpus_reader() -> FsShardReader {
+    let base = format!("{}/testdata/cicd-corpus", env!("CARGO_MANIFEST_DIR"));
+    FsShardReader {
+        disk_paths: (1..=5).map(|i| format!("{}/disk{}", base, i)).collect(),
+    }
+}
+
+/// Metadata of bucket/testobj in the corpus (EC 3+2, single part)
+pub(crate) fn corpus_meta() -> ObjectMeta {
+    let reader = corpus_reader();
+    let xl = std::fs::read(format!("{}/bucket/testobj/xl.meta", reader.disk_paths[1])).unwrap();
+    let mut meta = crate::xlmeta::parse(&xl).unwrap();
+    meta.bucket = "bucket".to_string();
+    meta.key = "testobj".to_string();
+    meta
+}
+
+/// Erasure-code `data` into streaming-bitrot shard files, one per shard.
+pub(crate) fn encode_shard_files(
+    data: &[u8],
+    data_blocks: usize,
+    parity_blocks: usize,
+    block_size: usize,
+) -> Vec<Vec<u8>> {
+    let rs = ReedSolomon::new(data_blocks, parity_blocks).unwrap();
+    let shard_size = block_size.div_ceil(data_blocks);
+    let mut writers: Vec<_> = (0..data_blocks + parity_blocks)
+        .map(|_| ShardWriter::new(Vec::new(), shard_size as i64))
+        .collect();
+    for block in data.chunks(block_size) {
+        let len = block.len().div_ceil(data_blocks);
+        let mut shards = vec![vec![0u8; len]; data_blocks + parity_blocks];
+        for (i, chunk) in block.chunks(len).enumerate() {
+            shards[i][..chunk.len()].copy_from_slice(chunk);
+        }
+        rs.encode(&mut shards).unwrap();
+        for (writer, shard) in writers.iter_mut().zip(&shards) {
+            writer.write_block(shard).unwrap();
+        }
+    }
+    writers.into_iter().map(|w| w.finish().unwrap().0).collect()
+}
+
+/// Multipart reader: `parts[part - 1][disk]`, counting range reads
+pub(crate) struct PartsMockReader {
+    pub(crate) parts: Vec<Vec<Option<Vec<u8>>>>,
+    pub(crate) range_reads: AtomicUsize,
+}
+
+impl ShardReader for PartsMockReader {
+    fn read_shard(
+        &self,
+        disk_index: usize,
+        _bucket: &str,
+        _key: &str,
+        _data_dir: &str,
+        part_number: i32,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self.parts[part_number as usize - 1][disk_index].clone())
+    }
+
+    fn read_shard_range(
+        &self,
+        disk_index: usize,
+        _bucket: &str,
+        _key: &str,
+        _data_dir: &str,
+        part_number: i32,
+        offset: u64,
+        len: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        self.range_reads.fetch_add(1, Ordering::Relaxed);
+        let shard = &self.parts[part_number as usize - 1][disk_index];
+        Ok(shard.as_ref().map(|data| {
+            let start = std::cmp::min(offset as usize, data.len());
+            let end = std::cmp::min(start + len, data.len());
+            data[start..end].to_vec()
+        }))
+    }
+}
+
+/// Two-part 2+1 object with 64-byte blocks and its reader
+pub(crate) fn multipart_object() -> (Vec<u8>, ObjectMeta, PartsMockReader) {
+    let data: Vec<u8> = (0..700u32).map(|i| (i * 13 % 253) as u8).collect();
+    let (part1, part2) = data.split_at(300);
+    let mut meta = ObjectMeta {
+        bucket: "test".to_string(),
+        key: "key".to_string(),
+        data_blocks: 2,
+        parity_blocks: 1,
+        block_size: 64,
+        size: data.len() as i64,
+        distribution: vec![2, 3, 1],
+        ..Default::default()
+    };
+    let mut parts = Vec::new();
+    for (i, part) in [part1, part2].iter().enumerate() {
+        meta.parts.push(PartMeta {
+            number: i as i32 + 1,
+            size: part.len() as i64,
+            actual_size: part.len() as i64,
+            ..Default::default()
+        });
+        let files = encode_shard_files(part, 2, 1, 64);
+        // Disk d holds shard distribution[d]
+        let disks = meta
+            .distribution
+            .iter()
+            .map(|&shard| Some(files[shard as usize - 1].clone()))
+            .collect();
+        parts.push(disks);
+    }
+    let reader = PartsMockReader {
+        parts,
+        range_reads: AtomicUsize::new(0),
+    };
+    (data, meta, reader)
+}
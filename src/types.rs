@@ -1,5 +1,10 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{ensure, Context};
+
+use crate::distribution::DistributionAlgo;
 
 /// 16-byte UUID as used by MinIO (raw bytes, not standard UUID format)
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -22,6 +27,23 @@ impl Uuid16 {
     pub fn is_zero(&self) -> bool {
         self.0 == [0u8; 16]
     }
+
+    /// Parse an S3 version id: "null" (or empty) is the null version, stored as all zeros
+    pub fn from_version_id(s: &str) -> anyhow::Result<Self> {
+        if s.is_empty() || s == "null" {
+            return Ok(Uuid16::default());
+        }
+        s.parse()
+    }
+
+    /// Format as an S3 version id: "null" for the null version, UUID string otherwise
+    pub fn to_version_id_string(&self) -> String {
+        if self.is_zero() {
+            "null".to_string()
+        } else {
+            self.to_uuid_string()
+        }
+    }
 }
 
 impl fmt::Debug for Uuid16 {
@@ -36,16 +58,142 @@ impl fmt::Display for Uuid16 {
     }
 }
 
+impl FromStr for Uuid16 {
+    type Err = anyhow::Error;
+
+    /// Parse a MinIO-style UUID string (the inverse of `to_uuid_string`)
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let raw = s.as_bytes();
+        ensure!(
+            raw.len() == 36 && [8, 13, 18, 23].iter().all(|&i| raw[i] == b'-'),
+            "invalid UUID string: {:?}",
+            s
+        );
+        let hex: Vec<u8> = raw.iter().copied().filter(|&c| c != b'-').collect();
+        // from_str_radix alone would accept a leading '+' in a pair
+        ensure!(
+            hex.len() == 32 && hex.iter().all(u8::is_ascii_hexdigit),
+            "invalid UUID string: {:?}",
+            s
+        );
+        let mut bytes = [0u8; 16];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            let pair = std::str::from_utf8(&hex[i * 2..i * 2 + 2])
+                .with_context(|| format!("invalid UUID string: {:?}", s))?;
+            *b = u8::from_str_radix(pair, 16)
+                .with_context(|| format!("invalid UUID string: {:?}", s))?;
+        }
+        Ok(Uuid16(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Uuid16 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_uuid_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Uuid16 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serde adapter writing byte strings as lowercase hex
+#[cfg(feature = "serde")]
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(D::Error::custom(format!("invalid hex string: {:?}", s)));
+        }
+        (0..s.len())
+            .step_by(2)
+            .map(|i| {
+                u8::from_str_radix(&s[i..i + 2], 16)
+                    .map_err(|_| D::Error::custom(format!("invalid hex string: {:?}", s)))
+            })
+            .collect()
+    }
+}
+
 /// Metadata for a single part of a multipart object
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartMeta {
     pub number: i32,
     pub size: i64,
     pub actual_size: i64,
+    /// Whole-file bitrot checksum of this disk's shard (empty for streaming algorithms)
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes", default))]
+    pub checksum: Vec<u8>,
+}
+
+/// Bitrot protection algorithm (MinIO's BitrotAlgorithm)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BitrotAlgorithm {
+    /// SHA-256 over the whole shard file
+    Sha256 = 1,
+    /// HighwayHash256 over the whole shard file
+    HighwayHash256 = 2,
+    /// HighwayHash256 per block, interleaved with the data (default since 2019)
+    #[default]
+    HighwayHash256S = 3,
+    /// BLAKE2b-512 over the whole shard file
+    Blake2b512 = 4,
+}
+
+impl BitrotAlgorithm {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(BitrotAlgorithm::Sha256),
+            2 => Some(BitrotAlgorithm::HighwayHash256),
+            3 => Some(BitrotAlgorithm::HighwayHash256S),
+            4 => Some(BitrotAlgorithm::Blake2b512),
+            _ => None,
+        }
+    }
+
+    /// Parse MinIO's algorithm name as used in xl.json ("sha256", "blake2b", ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sha256" => Some(BitrotAlgorithm::Sha256),
+            "highwayhash256" => Some(BitrotAlgorithm::HighwayHash256),
+            "highwayhash256S" => Some(BitrotAlgorithm::HighwayHash256S),
+            "blake2b" => Some(BitrotAlgorithm::Blake2b512),
+            _ => None,
+        }
+    }
+
+    /// Streaming algorithms store a hash before every block inside the shard file;
+    /// the others store one checksum per shard file in xl.meta.
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, BitrotAlgorithm::HighwayHash256S)
+    }
+
+    /// Digest size in bytes
+    pub fn digest_size(&self) -> usize {
+        match self {
+            BitrotAlgorithm::Blake2b512 => 64,
+            _ => 32,
+        }
+    }
 }
 
 /// Version type from xl.meta
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VersionType {
     #[default]
     Unknown = 0,
@@ -64,8 +212,9 @@ impl VersionType {
         }
     }
 
+    /// Whether the version holds object data, as a V2 or a legacy V1 object
     pub fn is_object(&self) -> bool {
-        matches!(self, VersionType::Object)
+        matches!(self, VersionType::Object | VersionType::Legacy)
     }
 
     pub fn is_delete_marker(&self) -> bool {
@@ -73,8 +222,12 @@ impl VersionType {
     }
 }
 
+/// Data directory name of legacy objects migrated from `xl.json`
+pub const LEGACY_DATA_DIR: &str = "legacy";
+
 /// Complete object metadata parsed from xl.meta
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectMeta {
     // Object identification
     pub bucket: String,
@@ -86,6 +239,9 @@ pub struct ObjectMeta {
     // Version info
     pub version_id: Uuid16,
     pub data_dir: Uuid16,
+    /// Parts live in the `legacy` directory of a V1 object; `data_dir` is zero
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub legacy_data_dir: bool,
 
     // Erasure configuration
     pub data_blocks: usize,
@@ -93,6 +249,7 @@ pub struct ObjectMeta {
     pub block_size: i64,
     pub erasure_index: usize, // 1-based
     pub distribution: Vec<u8>,
+    pub bitrot_algo: BitrotAlgorithm,
 
     // Parts
     pub parts: Vec<PartMeta>,
@@ -104,15 +261,24 @@ pub struct ObjectMeta {
     pub content_type: String,
     pub user_meta: HashMap<String, String>,
 
-    // Pool/set placement (filled in after cluster discovery)
+    // Pool/set placement (filled in by distribution::place_object)
     pub pool_index: i32,
     pub set_index: i32,
 }
 
 impl ObjectMeta {
-    /// Data directory as UUID string
+    /// Data directory name: a UUID string, or "legacy" for V1 objects stored there
     pub fn data_dir_string(&self) -> String {
-        self.data_dir.to_uuid_string()
+        if self.legacy_data_dir {
+            LEGACY_DATA_DIR.to_string()
+        } else {
+            self.data_dir.to_uuid_string()
+        }
+    }
+
+    /// Whether the parts are stored in a data directory (false for inline data)
+    pub fn has_data_dir(&self) -> bool {
+        self.legacy_data_dir || !self.data_dir.is_zero()
     }
 
     /// Size of each shard for a given block: ceil(block_size / data_blocks)
@@ -124,10 +290,17 @@ impl ObjectMeta {
     pub fn total_shards(&self) -> usize {
         self.data_blocks + self.parity_blocks
     }
+
+    /// Expected size of a `part.N` shard file for a part of `part_size` bytes.
+    /// See `ShardLayout` for per-block offsets.
+    pub fn shard_file_size(&self, part_size: i64) -> i64 {
+        crate::layout::ShardLayout::for_part_size(self, part_size).file_size() as i64
+    }
 }
 
 /// Cluster topology: all pools, each with erasure sets of disks
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClusterConfig {
     pub pools: Vec<PoolConfig>,
 }
@@ -140,14 +313,19 @@ impl ClusterConfig {
 
 /// A single pool within a cluster
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PoolConfig {
     pub pool_id: String,
     pub pool_index: usize,
     pub sets: Vec<Vec<DiskInfo>>,
+    /// Set placement algorithm from format.json (`xl.distributionAlgo`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub distribution_algo: DistributionAlgo,
 }
 
 /// Information about a single disk in the cluster
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiskInfo {
     pub uuid: String,
     pub pool_index: usize,
@@ -201,6 +379,88 @@ mod tests {
         assert!(format!("{:?}", uuid).contains("Uuid16"));
     }
 
+    #[test]
+    fn test_uuid16_from_str_roundtrip() {
+        let uuid = Uuid16([
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66,
+            0x77, 0x88,
+        ]);
+        let parsed: Uuid16 = uuid.to_uuid_string().parse().unwrap();
+        assert_eq!(parsed, uuid);
+    }
+
+    #[test]
+    fn test_uuid16_from_str_rejects_invalid() {
+        assert!("not-a-uuid".parse::<Uuid16>().is_err());
+        assert!("12345678-9abc-def0-1122-33445566778g"
+            .parse::<Uuid16>()
+            .is_err());
+        assert!("123456789abcdef01122334455667788"
+            .parse::<Uuid16>()
+            .is_err());
+        assert!("+1+23456-9abc-def0-1122-334455667788"
+            .parse::<Uuid16>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_uuid16_version_id_null() {
+        assert!(Uuid16::from_version_id("null").unwrap().is_zero());
+        assert!(Uuid16::from_version_id("").unwrap().is_zero());
+        assert_eq!(Uuid16::default().to_version_id_string(), "null");
+
+        let uuid = Uuid16([0xaa; 16]);
+        assert_eq!(
+            Uuid16::from_version_id(&uuid.to_version_id_string()).unwrap(),
+            uuid
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_object_meta_serde_roundtrip() {
+        let meta = ObjectMeta {
+            bucket: "bucket".into(),
+            key: "key".into(),
+            version_type: VersionType::Object,
+            data_dir: Uuid16([0xab; 16]),
+            parts: vec![PartMeta {
+                number: 1,
+                size: 10,
+                actual_size: 10,
+                checksum: vec![0x00, 0x1f, 0xab],
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json["data_dir"], "abababab-abab-abab-abab-abababababab");
+        assert_eq!(json["version_type"], "Object");
+        assert_eq!(json["parts"][0]["checksum"], "001fab");
+
+        let back: ObjectMeta = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(back.data_dir, meta.data_dir);
+        assert_eq!(back.parts.len(), 1);
+        assert_eq!(back.parts[0].checksum, meta.parts[0].checksum);
+
+        let mut bad = json;
+        bad["parts"][0]["checksum"] = "abc".into();
+        assert!(serde_json::from_value::<ObjectMeta>(bad).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_pool_config_serde_distribution_algo() {
+        let json = serde_json::json!({"pool_id": "p1", "pool_index": 0, "sets": []});
+        let pool: PoolConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(pool.distribution_algo, DistributionAlgo::CrcMod);
+
+        let mut json = json;
+        json["distribution_algo"] = "SIPMOD+PARITY".into();
+        let pool: PoolConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(pool.distribution_algo, DistributionAlgo::SipModParity);
+        assert_eq!(serde_json::to_value(&pool).unwrap(), json);
+    }
+
     #[test]
     fn test_uuid16_equality() {
         let a = Uuid16([1u8; 16]);
@@ -230,11 +490,33 @@ mod tests {
     #[test]
     fn test_version_type_predicates() {
         assert!(VersionType::Object.is_object());
+        assert!(VersionType::Legacy.is_object());
         assert!(!VersionType::DeleteMarker.is_object());
         assert!(VersionType::DeleteMarker.is_delete_marker());
         assert!(!VersionType::Object.is_delete_marker());
     }
 
+    #[test]
+    fn test_bitrot_algorithm_ids_and_names() {
+        assert_eq!(BitrotAlgorithm::from_u8(1), Some(BitrotAlgorithm::Sha256));
+        assert_eq!(
+            BitrotAlgorithm::from_u8(3),
+            Some(BitrotAlgorithm::HighwayHash256S)
+        );
+        assert_eq!(BitrotAlgorithm::from_u8(5), None);
+        assert_eq!(
+            BitrotAlgorithm::from_name("blake2b"),
+            Some(BitrotAlgorithm::Blake2b512)
+        );
+        assert_eq!(
+            BitrotAlgorithm::from_name("highwayhash256S"),
+            Some(BitrotAlgorithm::HighwayHash256S)
+        );
+        assert!(BitrotAlgorithm::default().is_streaming());
+        assert!(!BitrotAlgorithm::HighwayHash256.is_streaming());
+        assert_eq!(BitrotAlgorithm::Blake2b512.digest_size(), 64);
+    }
+
     #[test]
     fn test_object_meta_shard_size_calculation() {
         let meta = ObjectMeta {
@@ -255,6 +537,26 @@ mod tests {
         assert_eq!(meta.shard_size(), 100);
     }
 
+    #[test]
+    fn test_object_meta_shard_file_size() {
+        let mut meta = ObjectMeta {
+            block_size: 1048576,
+            data_blocks: 3,
+            ..Default::default()
+        };
+        // cicd-corpus testobj: 644520 bytes in one short block
+        assert_eq!(meta.shard_file_size(644520), 32 + 214840);
+        // Two full blocks plus a 1-byte tail
+        assert_eq!(
+            meta.shard_file_size(2 * 1048576 + 1),
+            2 * (32 + 349526) + 32 + 1
+        );
+        assert_eq!(meta.shard_file_size(0), 0);
+
+        meta.bitrot_algo = BitrotAlgorithm::Sha256;
+        assert_eq!(meta.shard_file_size(644520), 214840);
+    }
+
     #[test]
     fn test_object_meta_total_shards() {
         let meta = ObjectMeta {
@@ -301,11 +603,13 @@ mod tests {
                     pool_id: "p1".into(),
                     pool_index: 0,
                     sets: vec![vec![], vec![]],
+                    distribution_algo: DistributionAlgo::SipModParity,
                 },
                 PoolConfig {
                     pool_id: "p2".into(),
                     pool_index: 1,
                     sets: vec![vec![]],
+                    distribution_algo: DistributionAlgo::SipModParity,
                 },
             ],
         };
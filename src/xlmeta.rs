@@ -1,6 +1,7 @@
 //! xl.meta binary format parser
 //!
-//! Port of xlmeta/parser.go. Hand-rolled msgpack parsing using `rmp::decode`.
+//! Port of xlmeta/parser.go. Hand-rolled msgpack parsing using `rmp::decode`;
+//! `encode` and `merge_version` write copies back with `rmp::encode`.
 //!
 //! Format:
 //! ```text
@@ -11,18 +12,111 @@
 //! [msgpack u32: crc]  -- (xxh64(blob) & 0xFFFFFFFF) as u32
 //! [optional inline data]
 //! ```
+//!
+//! Metadata blob:
+//! ```text
+//! [u8: header version][u8: meta version][int: version count]
+//! per version: [msgpack bin: version header][msgpack bin: version meta]
+//! ```
+//!
+//! Inline data: `[u8: version = 1][msgpack map: version id string -> bin]`
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Cursor;
 
 use anyhow::{bail, ensure, Context, Result};
 use rmp::decode::{self, DecodeStringError};
+use rmp::encode;
 use xxhash_rust::xxh64;
 
-use crate::types::{ObjectMeta, PartMeta, VersionType};
+use crate::types::{BitrotAlgorithm, ObjectMeta, PartMeta, Uuid16, VersionType, LEGACY_DATA_DIR};
 
 const XL_HEADER: [u8; 4] = *b"XL2 ";
 
+/// Metadata blob header and meta versions written by `encode` (7-field version headers)
+const XL_HEADER_VERSION: u8 = 3;
+const XL_META_VERSION: u8 = 3;
+
+/// Version header flag: version is a free version (tiered object cleanup)
+pub const FLAG_FREE_VERSION: u8 = 1 << 0;
+/// Version header flag: version stores its data in a data directory
+pub const FLAG_USES_DATA_DIR: u8 = 1 << 1;
+/// Version header flag: version data is stored inline in xl.meta
+pub const FLAG_INLINE_DATA: u8 = 1 << 2;
+
+/// Supported inline data format version
+const XL_INLINE_DATA_VERSION: u8 = 1;
+
+/// Entry of the version index (xlMetaV2VersionHeader)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionHeader {
+    pub version_id: Uuid16,
+    pub mod_time: i64, // nanos since epoch
+    pub signature: [u8; 4],
+    pub version_type: VersionType,
+    pub flags: u8,
+    // Only present in header version 3+, zero otherwise
+    pub ec_n: u8,
+    pub ec_m: u8,
+}
+
+impl VersionHeader {
+    pub fn is_free_version(&self) -> bool {
+        self.flags & FLAG_FREE_VERSION != 0
+    }
+
+    pub fn uses_data_dir(&self) -> bool {
+        self.flags & FLAG_USES_DATA_DIR != 0
+    }
+
+    pub fn has_inline_data(&self) -> bool {
+        self.flags & FLAG_INLINE_DATA != 0
+    }
+}
+
+/// A single version from xl.meta: index header plus decoded version metadata
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XlVersion {
+    pub header: VersionHeader,
+    pub meta: ObjectMeta,
+    /// Internal system metadata (MetaSys), values are raw bytes
+    pub sys_meta: HashMap<String, Vec<u8>>,
+}
+
+/// Complete contents of an xl.meta file
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XlMeta {
+    pub major: u16,
+    pub minor: u16,
+    pub header_version: u8,
+    pub meta_version: u8,
+    /// All versions, latest first
+    pub versions: Vec<XlVersion>,
+    /// Inline data entries keyed by version id string ("null" for the null version)
+    pub inline_data: Vec<(String, Vec<u8>)>,
+}
+
+impl XlMeta {
+    /// Find a version by its id (all zeros for the null version)
+    pub fn find_version(&self, version_id: &Uuid16) -> Option<&XlVersion> {
+        self.versions
+            .iter()
+            .find(|v| v.header.version_id == *version_id)
+    }
+
+    /// Newest version with mod time <= `at` (nanos since epoch), skipping free versions.
+    /// Versions are stored latest first, so of versions with equal mod times the
+    /// first in header order wins, as in MinIO. The result may be a delete marker.
+    pub fn version_at(&self, at: i64) -> Option<&XlVersion> {
+        self.versions
+            .iter()
+            .find(|v| !v.header.is_free_version() && v.header.mod_time <= at)
+    }
+}
+
 /// Read a u8 value from cursor, handling both positive fixint and uint8 formats.
 /// This is a workaround for rmp::decode::read_u8 which seems to have issues.
 fn read_u8_value(cur: &mut Cursor<&[u8]>) -> Result<u8> {
@@ -49,6 +143,301 @@ fn read_u8_value(cur: &mut Cursor<&[u8]>) -> Result<u8> {
 
 /// Parse an xl.meta file and return object metadata.
 pub fn parse(data: &[u8]) -> Result<ObjectMeta> {
+    let payload = check_header(data)?;
+    let (meta_blob, _inline) = split_payload(payload)?;
+    parse_metadata_blob(meta_blob)
+}
+
+/// Parse an xl.meta file including every version, the version headers,
+/// system metadata and inline data.
+pub fn parse_full(data: &[u8]) -> Result<XlMeta> {
+    let payload = check_header(data)?;
+    let (meta_blob, inline) = split_payload(payload)?;
+
+    let mut cur = Cursor::new(meta_blob);
+    let header_version = read_u8_value(&mut cur).context("failed to read header version")?;
+    let meta_version = read_u8_value(&mut cur).context("failed to read meta version")?;
+    let count = read_int(&mut cur).context("failed to read version count")?;
+    ensure!(count >= 0, "negative version count: {}", count);
+
+    let mut versions = Vec::with_capacity(capped(&cur, count as u64));
+    for i in 0..count {
+        let hdr = read_bin_slice(&mut cur)
+            .with_context(|| format!("failed to read version header {}", i))?;
+        let ver_meta = read_bin_slice(&mut cur)
+            .with_context(|| format!("failed to read version meta {}", i))?;
+
+        let header = parse_version_header(hdr).with_context(|| format!("version header {}", i))?;
+        let mut sys_meta = HashMap::new();
+        let meta = parse_version_meta(ver_meta, &mut sys_meta)
+            .with_context(|| format!("version meta {}", i))?;
+        versions.push(XlVersion {
+            header,
+            meta,
+            sys_meta,
+        });
+    }
+
+    let inline_data = parse_inline_data(inline).context("failed to parse inline data")?;
+
+    Ok(XlMeta {
+        major: u16::from_le_bytes([data[4], data[5]]),
+        minor: u16::from_le_bytes([data[6], data[7]]),
+        header_version,
+        meta_version,
+        versions,
+        inline_data,
+    })
+}
+
+/// Serialize a single-version xl.meta copy for `meta`, with its `erasure_index`.
+///
+/// Only erasure-coded objects with streaming bitrot (V2Obj) can be written.
+/// `etag` and `content_type` go into the user metadata. The version signature
+/// is computed over the version with EcIndex cleared, so every disk's copy of
+/// the version carries the same signature.
+pub fn encode(meta: &ObjectMeta) -> Result<Vec<u8>> {
+    encode_version(meta, None)
+}
+
+/// Like `encode`, but with `shard` stored as the version's inline data
+#[cfg(test)]
+pub(crate) fn encode_inline(meta: &ObjectMeta, shard: &[u8]) -> Result<Vec<u8>> {
+    encode_version(meta, Some(shard))
+}
+
+fn encode_version(meta: &ObjectMeta, inline: Option<&[u8]>) -> Result<Vec<u8>> {
+    ensure!(
+        meta.bitrot_algo == BitrotAlgorithm::HighwayHash256S,
+        "cannot write {:?} bitrot in a V2 object",
+        meta.bitrot_algo
+    );
+    ensure!(
+        !meta.legacy_data_dir,
+        "cannot write a legacy data dir in a V2 object"
+    );
+    ensure!(
+        meta.data_blocks > 0 && meta.data_blocks + meta.parity_blocks <= u8::MAX as usize,
+        "invalid erasure config {}+{}",
+        meta.data_blocks,
+        meta.parity_blocks
+    );
+
+    let ver_meta = encode_version_meta(meta, meta.erasure_index)?;
+    let sig = xxh64::xxh64(&encode_version_meta(meta, 0)?, 0);
+    let sig = ((sig ^ (sig >> 32)) as u32).to_le_bytes();
+
+    let mut hdr = Vec::new();
+    encode::write_array_len(&mut hdr, 7)?;
+    encode::write_bin(&mut hdr, &meta.version_id.0)?;
+    encode::write_sint(&mut hdr, meta.mod_time)?;
+    encode::write_bin(&mut hdr, &sig)?;
+    encode::write_uint(&mut hdr, VersionType::Object as u64)?;
+    let flags = if inline.is_some() {
+        FLAG_INLINE_DATA
+    } else {
+        FLAG_USES_DATA_DIR
+    };
+    encode::write_uint(&mut hdr, flags as u64)?;
+    encode::write_uint(&mut hdr, meta.parity_blocks as u64)?;
+    encode::write_uint(&mut hdr, meta.data_blocks as u64)?;
+
+    let mut blob = Vec::new();
+    encode::write_uint(&mut blob, XL_HEADER_VERSION as u64)?;
+    encode::write_uint(&mut blob, XL_META_VERSION as u64)?;
+    encode::write_uint(&mut blob, 1)?;
+    encode::write_bin(&mut blob, &hdr)?;
+    encode::write_bin(&mut blob, &ver_meta)?;
+
+    let mut out = XL_HEADER.to_vec();
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&3u16.to_le_bytes());
+    write_blob(&mut out, &blob)?;
+    if let Some(shard) = inline {
+        out.push(XL_INLINE_DATA_VERSION);
+        encode::write_map_len(&mut out, 1)?;
+        encode::write_str(&mut out, &meta.version_id.to_version_id_string())?;
+        encode::write_bin(&mut out, shard)?;
+    }
+    Ok(out)
+}
+
+/// Append the metadata blob (bin32, as MinIO writes it) and its CRC
+fn write_blob(out: &mut Vec<u8>, blob: &[u8]) -> Result<()> {
+    out.push(rmp::Marker::Bin32.to_u8());
+    out.extend_from_slice(&(blob.len() as u32).to_be_bytes());
+    out.extend_from_slice(blob);
+    encode::write_u32(out, (xxh64::xxh64(blob, 0) & 0xFFFFFFFF) as u32)?;
+    Ok(())
+}
+
+/// Encode the xlMetaV2Version map of an object version
+fn encode_version_meta(meta: &ObjectMeta, ec_index: usize) -> Result<Vec<u8>> {
+    let mut b = Vec::new();
+    encode::write_map_len(&mut b, 2)?;
+    encode::write_str(&mut b, "Type")?;
+    encode::write_uint(&mut b, VersionType::Object as u64)?;
+    encode::write_str(&mut b, "V2Obj")?;
+    encode::write_map_len(&mut b, 16)?;
+
+    encode::write_str(&mut b, "ID")?;
+    encode::write_bin(&mut b, &meta.version_id.0)?;
+    encode::write_str(&mut b, "DDir")?;
+    encode::write_bin(&mut b, &meta.data_dir.0)?;
+    encode::write_str(&mut b, "EcAlgo")?;
+    encode::write_uint(&mut b, 1)?;
+    encode::write_str(&mut b, "EcM")?;
+    encode::write_uint(&mut b, meta.data_blocks as u64)?;
+    encode::write_str(&mut b, "EcN")?;
+    encode::write_uint(&mut b, meta.parity_blocks as u64)?;
+    encode::write_str(&mut b, "EcBSize")?;
+    encode::write_sint(&mut b, meta.block_size)?;
+    encode::write_str(&mut b, "EcIndex")?;
+    encode::write_uint(&mut b, ec_index as u64)?;
+    encode::write_str(&mut b, "EcDist")?;
+    encode::write_array_len(&mut b, meta.distribution.len() as u32)?;
+    for &d in &meta.distribution {
+        encode::write_uint(&mut b, d as u64)?;
+    }
+    encode::write_str(&mut b, "CSumAlgo")?;
+    encode::write_uint(&mut b, 1)?;
+
+    encode::write_str(&mut b, "PartNums")?;
+    encode::write_array_len(&mut b, meta.parts.len() as u32)?;
+    for p in &meta.parts {
+        encode::write_sint(&mut b, p.number as i64)?;
+    }
+    encode::write_str(&mut b, "PartETags")?;
+    encode::write_nil(&mut b)?;
+    encode::write_str(&mut b, "PartSizes")?;
+    encode::write_array_len(&mut b, meta.parts.len() as u32)?;
+    for p in &meta.parts {
+        encode::write_sint(&mut b, p.size)?;
+    }
+    encode::write_str(&mut b, "PartASizes")?;
+    encode::write_array_len(&mut b, meta.parts.len() as u32)?;
+    for p in &meta.parts {
+        encode::write_sint(&mut b, p.actual_size)?;
+    }
+
+    encode::write_str(&mut b, "Size")?;
+    encode::write_sint(&mut b, meta.size)?;
+    encode::write_str(&mut b, "MTime")?;
+    encode::write_sint(&mut b, meta.mod_time)?;
+
+    // Sorted so every disk's copy is byte-identical apart from EcIndex
+    let mut user_meta: BTreeMap<&str, &str> = meta
+        .user_meta
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    if !meta.etag.is_empty() {
+        user_meta.insert("etag", &meta.etag);
+    }
+    if !meta.content_type.is_empty() {
+        user_meta.insert("content-type", &meta.content_type);
+    }
+    encode::write_str(&mut b, "MetaUsr")?;
+    encode::write_map_len(&mut b, user_meta.len() as u32)?;
+    for (k, v) in user_meta {
+        encode::write_str(&mut b, k)?;
+        encode::write_str(&mut b, v)?;
+    }
+    Ok(b)
+}
+
+/// Build the xl.meta copy of `disk_index` after healing one version.
+///
+/// The version `version_id` is taken from `donor` (another disk's copy) with
+/// the EcIndex of `disk_index`, and merged into `target`, the disk's existing
+/// copy if it has one: its other versions and inline data are kept byte for
+/// byte, and the healed version replaces any entry with the same id. Versions
+/// stay ordered latest first. No other version of `donor` is copied, since
+/// their parts are not on the disk.
+pub fn merge_version(
+    target: Option<&[u8]>,
+    donor: &[u8],
+    version_id: &Uuid16,
+    disk_index: usize,
+) -> Result<Vec<u8>> {
+    let (donor_blob, _) = split_payload(check_header(donor)?)?;
+    let (header_version, meta_version, donor_versions) = raw_versions(donor_blob)?;
+    let (healed, hdr, ver_meta) = donor_versions
+        .into_iter()
+        .find(|(h, _, _)| h.version_id == *version_id)
+        .context("donor xl.meta does not hold the version")?;
+    let ver_meta = set_version_ec_index(ver_meta, disk_index).context("healed version meta")?;
+
+    let (prefix, mut versions, inline) = match target {
+        Some(target) => {
+            let (blob, inline) = split_payload(check_header(target)?)?;
+            let (hv, mv, versions) = raw_versions(blob)?;
+            ensure!(
+                (hv, mv) == (header_version, meta_version),
+                "xl.meta versions differ: target {}.{}, donor {}.{}",
+                hv,
+                mv,
+                header_version,
+                meta_version
+            );
+            (&target[..8], versions, inline)
+        }
+        None => (&donor[..8], Vec::new(), &[][..]),
+    };
+    versions.retain(|(h, _, _)| h.version_id != *version_id);
+    let at = versions
+        .iter()
+        .position(|(h, _, _)| h.mod_time < healed.mod_time)
+        .unwrap_or(versions.len());
+
+    let mut blob = Vec::new();
+    encode::write_uint(&mut blob, header_version as u64)?;
+    encode::write_uint(&mut blob, meta_version as u64)?;
+    encode::write_uint(&mut blob, versions.len() as u64 + 1)?;
+    for (i, (_, h, m)) in versions.iter().enumerate() {
+        if i == at {
+            encode::write_bin(&mut blob, hdr)?;
+            encode::write_bin(&mut blob, &ver_meta)?;
+        }
+        encode::write_bin(&mut blob, h)?;
+        encode::write_bin(&mut blob, m)?;
+    }
+    if at == versions.len() {
+        encode::write_bin(&mut blob, hdr)?;
+        encode::write_bin(&mut blob, &ver_meta)?;
+    }
+
+    let mut out = prefix.to_vec();
+    write_blob(&mut out, &blob)?;
+    out.extend_from_slice(inline);
+    Ok(out)
+}
+
+/// A version entry: its parsed header, then the raw header and meta bytes
+type RawVersion<'a> = (VersionHeader, &'a [u8], &'a [u8]);
+
+/// Header and meta versions of a metadata blob, and every version entry
+fn raw_versions(blob: &[u8]) -> Result<(u8, u8, Vec<RawVersion<'_>>)> {
+    let mut cur = Cursor::new(blob);
+    let header_version = read_u8_value(&mut cur).context("failed to read header version")?;
+    let meta_version = read_u8_value(&mut cur).context("failed to read meta version")?;
+    let count = read_int(&mut cur).context("failed to read version count")?;
+    ensure!(count >= 0, "negative version count: {}", count);
+
+    let mut versions = Vec::with_capacity(capped(&cur, count as u64));
+    for i in 0..count {
+        let hdr = read_bin_slice(&mut cur)
+            .with_context(|| format!("failed to read version header {}", i))?;
+        let ver_meta = read_bin_slice(&mut cur)
+            .with_context(|| format!("failed to read version meta {}", i))?;
+        let header = parse_version_header(hdr).with_context(|| format!("version header {}", i))?;
+        versions.push((header, hdr, ver_meta));
+    }
+    Ok((header_version, meta_version, versions))
+}
+
+/// Validate the file header and version, returning the payload after it
+fn check_header(data: &[u8]) -> Result<&[u8]> {
     ensure!(data.len() >= 8, "xl.meta too short: {} bytes", data.len());
 
     // Check header
@@ -71,11 +460,12 @@ pub fn parse(data: &[u8]) -> Result<ObjectMeta> {
         minor
     );
 
-    parse_v1_3(&data[8..])
+    Ok(&data[8..])
 }
 
-/// Parse xl.meta v1.3+ format (indexed)
-fn parse_v1_3(payload: &[u8]) -> Result<ObjectMeta> {
+/// Split an xl.meta v1.3+ payload (indexed) into the CRC-verified metadata
+/// blob and the trailing inline data
+fn split_payload(payload: &[u8]) -> Result<(&[u8], &[u8])> {
     let mut cur = Cursor::new(payload);
 
     // Read metadata blob (msgpack bin)
@@ -99,7 +489,7 @@ fn parse_v1_3(payload: &[u8]) -> Result<ObjectMeta> {
         crc
     );
 
-    parse_metadata_blob(meta_blob)
+    Ok((meta_blob, &payload[cur.position() as usize..]))
 }
 
 /// Parse the indexed metadata blob
@@ -128,11 +518,72 @@ fn parse_metadata_blob(blob: &[u8]) -> Result<ObjectMeta> {
     ensure!(meta_end <= blob.len(), "version meta extends beyond blob");
     let ver_meta = &blob[meta_start..meta_end];
 
-    parse_version_meta(ver_meta)
+    parse_version_meta(ver_meta, &mut HashMap::new())
 }
 
-/// Parse the xlMetaV2Version msgpack map
-fn parse_version_meta(data: &[u8]) -> Result<ObjectMeta> {
+/// Parse the xlMetaV2VersionHeader msgpack array
+fn parse_version_header(data: &[u8]) -> Result<VersionHeader> {
+    let mut cur = Cursor::new(data);
+    let mut header = VersionHeader::default();
+
+    let arr_len = decode::read_array_len(&mut cur).context("failed to read header array")?;
+    ensure!(
+        arr_len == 5 || arr_len == 7,
+        "unexpected version header field count: {}",
+        arr_len
+    );
+
+    let id = read_bin(&mut cur).context("failed to read VersionID")?;
+    ensure!(id.len() == 16, "invalid VersionID length: {}", id.len());
+    header.version_id.0.copy_from_slice(&id);
+
+    header.mod_time = read_i64(&mut cur).context("failed to read ModTime")?;
+
+    let sig = read_bin(&mut cur).context("failed to read Signature")?;
+    ensure!(sig.len() == 4, "invalid Signature length: {}", sig.len());
+    header.signature.copy_from_slice(&sig);
+
+    header.version_type =
+        VersionType::from_u8(read_u8_value(&mut cur).context("failed to read Type")?);
+    header.flags = read_u8_value(&mut cur).context("failed to read Flags")?;
+
+    if arr_len == 7 {
+        header.ec_n = read_u8_value(&mut cur).context("failed to read EcN")?;
+        header.ec_m = read_u8_value(&mut cur).context("failed to read EcM")?;
+    }
+
+    Ok(header)
+}
+
+/// Parse the inline data section that follows the metadata CRC
+fn parse_inline_data(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    ensure!(
+        data[0] == XL_INLINE_DATA_VERSION,
+        "unsupported inline data version: {}",
+        data[0]
+    );
+    if data.len() == 1 {
+        return Ok(Vec::new());
+    }
+
+    let mut cur = Cursor::new(&data[1..]);
+    let map_len = decode::read_map_len(&mut cur).context("failed to read inline data map")?;
+    let mut entries = Vec::with_capacity(capped(&cur, map_len as u64));
+    for _ in 0..map_len {
+        let key = read_string(&mut cur).context("failed to read inline data key")?;
+        let value =
+            read_bin(&mut cur).with_context(|| format!("failed to read inline data {}", key))?;
+        entries.push((key, value));
+    }
+
+    Ok(entries)
+}
+
+/// Parse the xlMetaV2Version msgpack map, collecting MetaSys into `sys_meta`
+fn parse_version_meta(data: &[u8], sys_meta: &mut HashMap<String, Vec<u8>>) -> Result<ObjectMeta> {
     let mut cur = Cursor::new(data);
     let mut meta = ObjectMeta::default();
 
@@ -147,11 +598,15 @@ fn parse_version_meta(data: &[u8]) -> Result<ObjectMeta> {
             "Type" => {
                 version_type = read_u8_value(&mut cur).context("failed to read Type")?;
             }
+            "V1Obj" => {
+                parse_v1_obj(&mut cur, &mut meta).context("failed to parse V1Obj")?;
+            }
             "V2Obj" => {
-                parse_v2_obj(&mut cur, &mut meta).context("failed to parse V2Obj")?;
+                parse_v2_obj(&mut cur, &mut meta, sys_meta).context("failed to parse V2Obj")?;
             }
             "V2DelObj" => {
-                parse_v2_del_obj(&mut cur, &mut meta).context("failed to parse V2DelObj")?;
+                parse_v2_del_obj(&mut cur, &mut meta, sys_meta)
+                    .context("failed to parse V2DelObj")?;
             }
             _ => {
                 skip_value(&mut cur).with_context(|| format!("failed to skip field {}", key))?;
@@ -163,8 +618,95 @@ fn parse_version_meta(data: &[u8]) -> Result<ObjectMeta> {
     Ok(meta)
 }
 
+/// Re-encode an xlMetaV2Version map with the V2Obj EcIndex set for `disk_index`
+fn set_version_ec_index(data: &[u8], disk_index: usize) -> Result<Vec<u8>> {
+    let mut cur = Cursor::new(data);
+    let map_len = decode::read_map_len(&mut cur).context("failed to read version map header")?;
+    let mut out = Vec::with_capacity(data.len());
+    encode::write_map_len(&mut out, map_len)?;
+
+    for _ in 0..map_len {
+        let start = cur.position() as usize;
+        let key = read_string(&mut cur).context("failed to read map key")?;
+        match key.as_str() {
+            "V2Obj" => {
+                out.extend_from_slice(&data[start..cur.position() as usize]);
+                set_v2_obj_ec_index(&mut cur, &mut out, disk_index)
+                    .context("failed to rewrite V2Obj")?;
+            }
+            "V1Obj" => bail!("legacy V1Obj versions cannot be rewritten"),
+            _ => {
+                skip_value(&mut cur).with_context(|| format!("failed to skip field {}", key))?;
+                out.extend_from_slice(&data[start..cur.position() as usize]);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Copy an xlMetaV2Object map into `out`, replacing EcIndex with the
+/// EcDist entry of `disk_index`
+fn set_v2_obj_ec_index(
+    cur: &mut Cursor<&[u8]>,
+    out: &mut Vec<u8>,
+    disk_index: usize,
+) -> Result<()> {
+    let map_len = decode::read_map_len(cur).context("failed to read V2Obj map header")?;
+    encode::write_map_len(out, map_len)?;
+
+    // Raw field bytes, with the EcIndex value left out; EcDist may follow it
+    let mut fields: Vec<(bool, &[u8])> = Vec::with_capacity(capped(cur, map_len as u64));
+    let mut ec_index = None;
+    for _ in 0..map_len {
+        let start = cur.position() as usize;
+        let key = read_string(cur).context("failed to read V2Obj key")?;
+        let value_start = cur.position() as usize;
+        match key.as_str() {
+            "EcIndex" => {
+                read_int(cur).context("failed to read EcIndex")?;
+                fields.push((true, &cur.get_ref()[start..value_start]));
+                continue;
+            }
+            "EcDist" => {
+                let arr_len =
+                    decode::read_array_len(cur).context("failed to read EcDist header")?;
+                for j in 0..arr_len {
+                    let v = read_u8_value(cur)
+                        .with_context(|| format!("failed to read EcDist[{}]", j))?;
+                    if j as usize == disk_index {
+                        ec_index = Some(v);
+                    }
+                }
+            }
+            _ => {
+                skip_value(cur).with_context(|| format!("failed to skip V2Obj field {}", key))?;
+            }
+        }
+        fields.push((false, &cur.get_ref()[start..cur.position() as usize]));
+    }
+
+    ensure!(
+        fields.iter().any(|(is_index, _)| *is_index),
+        "V2Obj has no EcIndex"
+    );
+    let ec_index =
+        ec_index.with_context(|| format!("EcDist has no entry for disk {}", disk_index))?;
+    for (is_index, raw) in fields {
+        out.extend_from_slice(raw);
+        if is_index {
+            encode::write_uint(out, ec_index as u64)?;
+        }
+    }
+    Ok(())
+}
+
 /// Parse the xlMetaV2Object msgpack map inline, filling `meta`
-fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
+fn parse_v2_obj(
+    cur: &mut Cursor<&[u8]>,
+    meta: &mut ObjectMeta,
+    sys_meta: &mut HashMap<String, Vec<u8>>,
+) -> Result<()> {
     let map_len = decode::read_map_len(cur).context("failed to read V2Obj map header")?;
 
     let mut part_numbers: Vec<i32> = Vec::new();
@@ -205,7 +747,7 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
             "EcDist" => {
                 let arr_len =
                     decode::read_array_len(cur).context("failed to read EcDist header")?;
-                meta.distribution = Vec::with_capacity(arr_len as usize);
+                meta.distribution = Vec::with_capacity(capped(cur, arr_len as u64));
                 for j in 0..arr_len {
                     let v = read_u8_value(cur)
                         .with_context(|| format!("failed to read EcDist[{}]", j))?;
@@ -213,12 +755,15 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
                 }
             }
             "CSumAlgo" => {
-                let _algo = read_u8_value(cur).context("failed to read CSumAlgo")?;
+                // ChecksumAlgo: 1 = HighwayHash, always streaming in v2 objects
+                let algo = read_u8_value(cur).context("failed to read CSumAlgo")?;
+                ensure!(algo == 1, "unsupported CSumAlgo {}", algo);
+                meta.bitrot_algo = BitrotAlgorithm::HighwayHash256S;
             }
             "PartNums" => {
                 let arr_len =
                     decode::read_array_len(cur).context("failed to read PartNums header")?;
-                part_numbers = Vec::with_capacity(arr_len as usize);
+                part_numbers = Vec::with_capacity(capped(cur, arr_len as u64));
                 for j in 0..arr_len {
                     let v =
                         read_int(cur).with_context(|| format!("failed to read PartNums[{}]", j))?;
@@ -228,7 +773,7 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
             "PartSizes" => {
                 let arr_len =
                     decode::read_array_len(cur).context("failed to read PartSizes header")?;
-                part_sizes = Vec::with_capacity(arr_len as usize);
+                part_sizes = Vec::with_capacity(capped(cur, arr_len as u64));
                 for j in 0..arr_len {
                     let v = read_i64(cur)
                         .with_context(|| format!("failed to read PartSizes[{}]", j))?;
@@ -238,7 +783,7 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
             "PartASizes" => {
                 match decode::read_array_len(cur) {
                     Ok(arr_len) => {
-                        let mut sizes = Vec::with_capacity(arr_len as usize);
+                        let mut sizes = Vec::with_capacity(capped(cur, arr_len as u64));
                         for j in 0..arr_len {
                             let v = read_i64(cur)
                                 .with_context(|| format!("failed to read PartASizes[{}]", j))?;
@@ -258,6 +803,9 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
             "MTime" => {
                 meta.mod_time = read_i64(cur).context("failed to read MTime")?;
             }
+            "MetaSys" => {
+                *sys_meta = parse_bytes_map(cur).context("failed to read MetaSys")?;
+            }
             "MetaUsr" => {
                 meta.user_meta = parse_string_map(cur).context("failed to read MetaUsr")?;
                 if let Some(ct) = meta.user_meta.get("content-type") {
@@ -286,6 +834,7 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
                 number: num,
                 size,
                 actual_size,
+                ..Default::default()
             });
         }
     }
@@ -293,8 +842,204 @@ fn parse_v2_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
     Ok(())
 }
 
+/// Parse a legacy xlMetaV1Object msgpack map (objects written in the xl.json era),
+/// filling `meta`. Legacy objects may use whole-file bitrot checksums, which are
+/// stored per part in `Erasure.Checksums`.
+fn parse_v1_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
+    let map_len = decode::read_map_len(cur).context("failed to read V1Obj map header")?;
+    let mut checksums: Vec<(i32, BitrotAlgorithm, Vec<u8>)> = Vec::new();
+
+    for _ in 0..map_len {
+        let key = read_string(cur).context("failed to read V1Obj key")?;
+
+        match key.as_str() {
+            "Stat" => {
+                let len = decode::read_map_len(cur).context("failed to read Stat header")?;
+                for _ in 0..len {
+                    let field = read_string(cur).context("failed to read Stat key")?;
+                    match field.as_str() {
+                        "Size" => meta.size = read_i64(cur).context("failed to read Size")?,
+                        "ModTime" => {
+                            meta.mod_time = read_time(cur).context("failed to read ModTime")?
+                        }
+                        _ => skip_value(cur)?,
+                    }
+                }
+            }
+            "Erasure" => {
+                checksums = parse_v1_erasure(cur, meta).context("failed to parse Erasure")?;
+            }
+            "Meta" => {
+                meta.user_meta = parse_string_map(cur).context("failed to read Meta")?;
+                if let Some(ct) = meta.user_meta.get("content-type") {
+                    meta.content_type = ct.clone();
+                }
+                if let Some(etag) = meta.user_meta.get("etag") {
+                    meta.etag = etag.clone();
+                }
+            }
+            "Parts" => {
+                let len = decode::read_array_len(cur).context("failed to read Parts header")?;
+                meta.parts = Vec::with_capacity(capped(cur, len as u64));
+                for j in 0..len {
+                    let part = parse_v1_part(cur).with_context(|| format!("Parts[{}]", j))?;
+                    meta.parts.push(part);
+                }
+            }
+            "VersionID" => {
+                let id = read_string(cur).context("failed to read VersionID")?;
+                meta.version_id = Uuid16::from_version_id(&id)?;
+            }
+            "DataDir" => {
+                // Legacy objects without a data directory use the name "legacy"
+                let ddir = read_string(cur).context("failed to read DataDir")?;
+                if ddir == LEGACY_DATA_DIR {
+                    meta.legacy_data_dir = true;
+                } else if !ddir.is_empty() {
+                    meta.data_dir = ddir
+                        .parse()
+                        .with_context(|| format!("invalid DataDir {:?}", ddir))?;
+                }
+            }
+            _ => {
+                skip_value(cur).with_context(|| format!("failed to skip V1Obj field {}", key))?;
+            }
+        }
+    }
+
+    for (number, algo, hash) in checksums {
+        meta.bitrot_algo = algo;
+        if let Some(part) = meta.parts.iter_mut().find(|p| p.number == number) {
+            part.checksum = hash;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the ErasureInfo map of a legacy object, returning its checksums as
+/// (part number, algorithm, hash)
+fn parse_v1_erasure(
+    cur: &mut Cursor<&[u8]>,
+    meta: &mut ObjectMeta,
+) -> Result<Vec<(i32, BitrotAlgorithm, Vec<u8>)>> {
+    let map_len = decode::read_map_len(cur).context("failed to read Erasure map header")?;
+    let mut checksums = Vec::new();
+
+    for _ in 0..map_len {
+        let key = read_string(cur).context("failed to read Erasure key")?;
+
+        match key.as_str() {
+            "DataBlocks" => meta.data_blocks = read_usize(cur).context("DataBlocks")?,
+            "ParityBlocks" => meta.parity_blocks = read_usize(cur).context("ParityBlocks")?,
+            "BlockSize" => meta.block_size = read_i64(cur)?,
+            "Index" => meta.erasure_index = read_usize(cur).context("Index")?,
+            "Distribution" => {
+                let len = decode::read_array_len(cur).context("failed to read Distribution")?;
+                meta.distribution = Vec::with_capacity(capped(cur, len as u64));
+                for _ in 0..len {
+                    let v = read_int(cur)?;
+                    let v = u8::try_from(v)
+                        .with_context(|| format!("Distribution entry {} out of range", v))?;
+                    meta.distribution.push(v);
+                }
+            }
+            "Checksums" => {
+                // Omitted as nil when empty
+                if peek_byte(cur, cur.position() as usize)? == 0xc0 {
+                    cur.set_position(cur.position() + 1);
+                    continue;
+                }
+                let len = decode::read_array_len(cur).context("failed to read Checksums")?;
+                for j in 0..len {
+                    let checksum = parse_v1_checksum(cur)
+                        .with_context(|| format!("failed to read Checksums[{}]", j))?;
+                    checksums.push(checksum);
+                }
+            }
+            _ => {
+                skip_value(cur).with_context(|| format!("failed to skip Erasure field {}", key))?;
+            }
+        }
+    }
+
+    // Both are 1-based disk positions within the erasure set
+    let total = meta
+        .data_blocks
+        .checked_add(meta.parity_blocks)
+        .context("DataBlocks + ParityBlocks overflows")?;
+    ensure!(
+        (1..=total).contains(&meta.erasure_index),
+        "Index {} out of range 1..={}",
+        meta.erasure_index,
+        total
+    );
+    if let Some(&d) = meta
+        .distribution
+        .iter()
+        .find(|&&d| !(1..=total).contains(&(d as usize)))
+    {
+        bail!("Distribution entry {} out of range 1..={}", d, total);
+    }
+
+    Ok(checksums)
+}
+
+/// Parse a ChecksumInfo map: {PartNumber, Algorithm, Hash}
+fn parse_v1_checksum(cur: &mut Cursor<&[u8]>) -> Result<(i32, BitrotAlgorithm, Vec<u8>)> {
+    let map_len = decode::read_map_len(cur)?;
+    let mut number = 0;
+    let mut algo = None;
+    let mut hash = Vec::new();
+
+    for _ in 0..map_len {
+        let key = read_string(cur)?;
+        match key.as_str() {
+            "PartNumber" => number = read_i32(cur).context("PartNumber")?,
+            "Algorithm" => {
+                let v = read_int(cur)?;
+                algo = Some(
+                    u8::try_from(v)
+                        .ok()
+                        .and_then(BitrotAlgorithm::from_u8)
+                        .with_context(|| format!("unknown bitrot algorithm {}", v))?,
+                );
+            }
+            "Hash" => hash = read_bin(cur)?,
+            _ => skip_value(cur)?,
+        }
+    }
+
+    let algo = algo.context("checksum without algorithm")?;
+    Ok((number, algo, hash))
+}
+
+/// Parse an ObjectPartInfo map of a legacy object
+fn parse_v1_part(cur: &mut Cursor<&[u8]>) -> Result<PartMeta> {
+    let map_len = decode::read_map_len(cur)?;
+    let mut part = PartMeta::default();
+    let mut actual_size = None;
+
+    for _ in 0..map_len {
+        let key = read_string(cur)?;
+        match key.as_str() {
+            "Number" => part.number = read_i32(cur).context("Number")?,
+            "Size" => part.size = read_i64(cur)?,
+            "ActualSize" => actual_size = Some(read_i64(cur)?),
+            _ => skip_value(cur)?,
+        }
+    }
+
+    part.actual_size = actual_size.unwrap_or(part.size);
+    Ok(part)
+}
+
 /// Parse the xlMetaV2DeleteMarker msgpack map (for delete markers)
-fn parse_v2_del_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()> {
+fn parse_v2_del_obj(
+    cur: &mut Cursor<&[u8]>,
+    meta: &mut ObjectMeta,
+    sys_meta: &mut HashMap<String, Vec<u8>>,
+) -> Result<()> {
     let map_len = decode::read_map_len(cur).context("failed to read V2DelObj map header")?;
 
     for _ in 0..map_len {
@@ -311,8 +1056,7 @@ fn parse_v2_del_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()
                 meta.mod_time = read_i64(cur).context("failed to read MTime")?;
             }
             "MetaSys" => {
-                // System metadata - skip for now, we don't need it for delete markers
-                skip_value(cur).context("failed to skip MetaSys")?;
+                *sys_meta = parse_bytes_map(cur).context("failed to read MetaSys")?;
             }
             _ => {
                 skip_value(cur)
@@ -327,7 +1071,7 @@ fn parse_v2_del_obj(cur: &mut Cursor<&[u8]>, meta: &mut ObjectMeta) -> Result<()
 /// Parse a msgpack map[string]string, handling both StrType and BinType values.
 fn parse_string_map(cur: &mut Cursor<&[u8]>) -> Result<HashMap<String, String>> {
     let map_len = decode::read_map_len(cur)?;
-    let mut result = HashMap::with_capacity(map_len as usize);
+    let mut result = HashMap::with_capacity(capped(cur, map_len as u64));
 
     for _ in 0..map_len {
         let key = read_string(cur)?;
@@ -364,6 +1108,35 @@ fn parse_string_map(cur: &mut Cursor<&[u8]>) -> Result<HashMap<String, String>>
     Ok(result)
 }
 
+/// Parse a msgpack map[string][]byte (MetaSys), accepting str values as well.
+fn parse_bytes_map(cur: &mut Cursor<&[u8]>) -> Result<HashMap<String, Vec<u8>>> {
+    // MetaSys is omitted as nil when empty
+    if peek_byte(cur, cur.position() as usize)? == 0xc0 {
+        cur.set_position(cur.position() + 1);
+        return Ok(HashMap::new());
+    }
+
+    let map_len = decode::read_map_len(cur)?;
+    let mut result = HashMap::with_capacity(capped(cur, map_len as u64));
+
+    for _ in 0..map_len {
+        let key = read_string(cur)?;
+        let marker = peek_byte(cur, cur.position() as usize)?;
+        let val = if is_bin_marker(marker) {
+            read_bin(cur)?
+        } else if is_str_marker(marker) {
+            read_string(cur)?.into_bytes()
+        } else {
+            // nil or unknown type — skip
+            skip_value(cur)?;
+            continue;
+        };
+        result.insert(key, val);
+    }
+
+    Ok(result)
+}
+
 // --- msgpack helper functions ---
 
 /// Read a msgpack integer (handles int/uint of various sizes)
@@ -450,6 +1223,46 @@ fn read_i64(cur: &mut Cursor<&[u8]>) -> Result<i64> {
     read_int(cur)
 }
 
+/// Capacity for `count` entries announced by the file, capped at the bytes
+/// left in `cur` (every entry takes at least one), so a corrupt count cannot
+/// request a huge allocation
+fn capped(cur: &Cursor<&[u8]>, count: u64) -> usize {
+    let remaining = (cur.get_ref().len() as u64).saturating_sub(cur.position());
+    count.min(remaining) as usize
+}
+
+/// Read a msgpack timestamp (msgp time extension, type 5) as nanos since epoch
+fn read_time(cur: &mut Cursor<&[u8]>) -> Result<i64> {
+    let pos = cur.position() as usize;
+    // ext8 with 12 bytes: [0xc7][12][type 5][i64 BE seconds][u32 BE nanos]
+    ensure!(
+        peek_byte(cur, pos)? == 0xc7 && peek_byte(cur, pos + 1)? == 12,
+        "expected time extension"
+    );
+    ensure!(
+        peek_byte(cur, pos + 2)? == 5,
+        "unexpected extension type for time"
+    );
+    let secs = i64::from_be_bytes(peek_bytes_8(cur, pos + 3)?);
+    let nanos = u32::from_be_bytes(peek_bytes_4(cur, pos + 11)?);
+    cur.set_position(pos as u64 + 15);
+    secs.checked_mul(1_000_000_000)
+        .and_then(|ns| ns.checked_add(nanos as i64))
+        .with_context(|| format!("time {}s out of range", secs))
+}
+
+/// Read a msgpack integer that must fit in an i32
+fn read_i32(cur: &mut Cursor<&[u8]>) -> Result<i32> {
+    let v = read_int(cur)?;
+    i32::try_from(v).with_context(|| format!("integer {} out of range", v))
+}
+
+/// Read a non-negative msgpack integer as usize
+fn read_usize(cur: &mut Cursor<&[u8]>) -> Result<usize> {
+    let v = read_int(cur)?;
+    usize::try_from(v).with_context(|| format!("integer {} out of range", v))
+}
+
 /// Read a msgpack string
 fn read_string(cur: &mut Cursor<&[u8]>) -> Result<String> {
     let mut buf = vec![0u8; 256];
@@ -489,6 +1302,17 @@ fn read_bin(cur: &mut Cursor<&[u8]>) -> Result<Vec<u8>> {
     Ok(result)
 }
 
+/// Read a msgpack binary blob as a slice of the underlying buffer
+fn read_bin_slice<'a>(cur: &mut Cursor<&'a [u8]>) -> Result<&'a [u8]> {
+    let len = decode::read_bin_len(cur).context("failed to read bin length")?;
+    let pos = cur.position() as usize;
+    let end = pos + len as usize;
+    let data: &'a [u8] = cur.get_ref();
+    ensure!(end <= data.len(), "bin data extends beyond buffer");
+    cur.set_position(end as u64);
+    Ok(&data[pos..end])
+}
+
 /// Skip a single msgpack value (any type).
 ///
 /// We read bytes via a helper `peek_bytes` that borrows `cur` only briefly,
@@ -988,6 +1812,232 @@ mod tests {
         );
     }
 
+    /// Test parsing all versions from cicd-corpus disk4, which holds two versions.
+    #[test]
+    fn test_parse_full_cicd_corpus_disk4_versions() {
+        let data = read_fixture("cicd-corpus/disk4/bucket/testobj/xl.meta");
+        let xl = parse_full(&data).expect("failed to parse disk4 xl.meta");
+
+        assert_eq!((xl.major, xl.minor), (1, 3));
+        assert_eq!(xl.versions.len(), 2, "disk4 should have 2 versions");
+        assert!(xl.inline_data.is_empty());
+
+        // Latest version first, and it matches what `parse` returns
+        let latest = parse(&data).unwrap();
+        assert_eq!(xl.versions[0].meta.version_id, latest.version_id);
+        assert_eq!(xl.versions[0].header.version_id, latest.version_id);
+        assert!(xl.versions[0].header.mod_time > xl.versions[1].header.mod_time);
+
+        // The older version is the one disk2/disk3 still have as latest
+        let disk2 = parse(&read_fixture("cicd-corpus/disk2/bucket/testobj/xl.meta")).unwrap();
+        assert_eq!(xl.versions[1].meta.version_id, disk2.version_id);
+
+        for v in &xl.versions {
+            assert_eq!(v.header.version_type, VersionType::Object);
+            assert!(v.header.uses_data_dir());
+            assert!(!v.header.has_inline_data());
+            assert_eq!(v.header.mod_time, v.meta.mod_time);
+            assert_eq!(
+                v.sys_meta
+                    .get("x-minio-internal-replica-status")
+                    .map(Vec::as_slice),
+                Some(&b"REPLICA"[..])
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_version() {
+        let data = read_fixture("cicd-corpus/disk4/bucket/testobj/xl.meta");
+        let xl = parse_full(&data).unwrap();
+
+        let older = Uuid16::from_version_id("50051050-62bc-4928-b0d3-65f4f828a0c7").unwrap();
+        let found = xl
+            .find_version(&older)
+            .expect("older version should be present");
+        assert_eq!(found.meta.version_id, older);
+
+        assert!(xl.find_version(&Uuid16::default()).is_none());
+    }
+
+    #[test]
+    fn test_version_at() {
+        let data = read_fixture("cicd-corpus/disk4/bucket/testobj/xl.meta");
+        let xl = parse_full(&data).unwrap();
+        let latest = xl.versions[0].header.mod_time;
+        let older = xl.versions[1].header.mod_time;
+
+        assert!(xl.version_at(older - 1).is_none());
+        assert_eq!(
+            xl.version_at(older).unwrap().header.version_id,
+            xl.versions[1].header.version_id
+        );
+        assert_eq!(
+            xl.version_at(latest - 1).unwrap().header.version_id,
+            xl.versions[1].header.version_id
+        );
+        assert_eq!(
+            xl.version_at(i64::MAX).unwrap().header.version_id,
+            xl.versions[0].header.version_id
+        );
+
+        // Equal mod times: the first version in header order is the newer one
+        let mut xl = xl;
+        xl.versions[1].header.mod_time = latest;
+        assert_eq!(
+            xl.version_at(latest).unwrap().header.version_id,
+            xl.versions[0].header.version_id
+        );
+    }
+
+    #[test]
+    fn test_set_version_ec_index_rejects_legacy() {
+        assert!(set_version_ec_index(&legacy_version_meta(), 0).is_err());
+    }
+
+    /// Build a legacy V1Obj version meta with whole-file SHA256 checksums
+    fn legacy_version_meta() -> Vec<u8> {
+        legacy_version_meta_with(1, &[2, 3, 4, 1], 1_600_000_000)
+    }
+
+    /// Legacy V1Obj for disk `index` of a 2+2 set
+    fn legacy_version_meta_with(index: i64, distribution: &[i64], mod_secs: i64) -> Vec<u8> {
+        use rmp::encode::*;
+        let mut b = Vec::new();
+        write_map_len(&mut b, 2).unwrap();
+        write_str(&mut b, "Type").unwrap();
+        write_uint(&mut b, 3).unwrap();
+        write_str(&mut b, "V1Obj").unwrap();
+        write_map_len(&mut b, 5).unwrap();
+
+        write_str(&mut b, "Stat").unwrap();
+        write_map_len(&mut b, 2).unwrap();
+        write_str(&mut b, "Size").unwrap();
+        write_sint(&mut b, 1000).unwrap();
+        write_str(&mut b, "ModTime").unwrap();
+        write_ext_meta(&mut b, 12, 5).unwrap();
+        b.extend_from_slice(&mod_secs.to_be_bytes());
+        b.extend_from_slice(&500u32.to_be_bytes());
+
+        write_str(&mut b, "Erasure").unwrap();
+        write_map_len(&mut b, 7).unwrap();
+        write_str(&mut b, "Algorithm").unwrap();
+        write_str(&mut b, "klauspost/reedsolomon/vandermonde").unwrap();
+        write_str(&mut b, "DataBlocks").unwrap();
+        write_uint(&mut b, 2).unwrap();
+        write_str(&mut b, "ParityBlocks").unwrap();
+        write_uint(&mut b, 2).unwrap();
+        write_str(&mut b, "BlockSize").unwrap();
+        write_uint(&mut b, 10485760).unwrap();
+        write_str(&mut b, "Index").unwrap();
+        write_sint(&mut b, index).unwrap();
+        write_str(&mut b, "Distribution").unwrap();
+        write_array_len(&mut b, distribution.len() as u32).unwrap();
+        for &d in distribution {
+            write_sint(&mut b, d).unwrap();
+        }
+        write_str(&mut b, "Checksums").unwrap();
+        write_array_len(&mut b, 1).unwrap();
+        write_map_len(&mut b, 3).unwrap();
+        write_str(&mut b, "PartNumber").unwrap();
+        write_uint(&mut b, 1).unwrap();
+        write_str(&mut b, "Algorithm").unwrap();
+        write_uint(&mut b, 1).unwrap();
+        write_str(&mut b, "Hash").unwrap();
+        write_bin(&mut b, &[0xab; 32]).unwrap();
+
+        write_str(&mut b, "Meta").unwrap();
+        write_map_len(&mut b, 1).unwrap();
+        write_str(&mut b, "etag").unwrap();
+        write_str(&mut b, "abc").unwrap();
+
+        write_str(&mut b, "Parts").unwrap();
+        write_array_len(&mut b, 1).unwrap();
+        write_map_len(&mut b, 2).unwrap();
+        write_str(&mut b, "Number").unwrap();
+        write_uint(&mut b, 1).unwrap();
+        write_str(&mut b, "Size").unwrap();
+        write_uint(&mut b, 1000).unwrap();
+
+        write_str(&mut b, "DataDir").unwrap();
+        write_str(&mut b, "legacy").unwrap();
+        b
+    }
+
+    #[test]
+    fn test_parse_legacy_v1_object_checksums() {
+        let meta = parse_version_meta(&legacy_version_meta(), &mut HashMap::new()).unwrap();
+
+        assert_eq!(meta.version_type, VersionType::Legacy);
+        assert_eq!(meta.size, 1000);
+        assert_eq!(meta.mod_time, 1_600_000_000_000_000_500);
+        assert_eq!((meta.data_blocks, meta.parity_blocks), (2, 2));
+        assert_eq!(meta.distribution, vec![2, 3, 4, 1]);
+        assert_eq!(meta.etag, "abc");
+        assert!(meta.legacy_data_dir);
+        assert_eq!(meta.data_dir_string(), "legacy");
+        assert!(meta.has_data_dir());
+
+        assert_eq!(meta.bitrot_algo, BitrotAlgorithm::Sha256);
+        assert_eq!(meta.parts.len(), 1);
+        assert_eq!(meta.parts[0].actual_size, 1000);
+        assert_eq!(meta.parts[0].checksum, vec![0xab; 32]);
+    }
+
+    #[test]
+    fn test_parse_legacy_v1_object_rejects_bad_erasure_info() {
+        let parse_with = |index, distribution: &[i64], mod_secs| {
+            let data = legacy_version_meta_with(index, distribution, mod_secs);
+            parse_version_meta(&data, &mut HashMap::new())
+        };
+        assert_eq!(parse_with(4, &[2, 3, 4, 1], 0).unwrap().erasure_index, 4);
+
+        // Index and Distribution entries are 1-based positions in the 2+2 set
+        assert!(parse_with(0, &[2, 3, 4, 1], 0).is_err());
+        assert!(parse_with(5, &[2, 3, 4, 1], 0).is_err());
+        assert!(parse_with(-1, &[2, 3, 4, 1], 0).is_err());
+        assert!(parse_with(1, &[2, 3, 0, 1], 0).is_err());
+        assert!(parse_with(1, &[2, 3, 5, 1], 0).is_err());
+        assert!(parse_with(1, &[2, 3, 257, 1], 0).is_err());
+
+        // ModTime that overflows i64 nanoseconds
+        assert!(parse_with(1, &[2, 3, 4, 1], i64::MAX / 1_000).is_err());
+    }
+
+    #[test]
+    fn test_parse_v2_object_uses_streaming_bitrot() {
+        let data = read_fixture("cicd-corpus/disk2/bucket/testobj/xl.meta");
+        let meta = parse(&data).unwrap();
+        assert_eq!(meta.bitrot_algo, BitrotAlgorithm::HighwayHash256S);
+        assert!(meta.parts[0].checksum.is_empty());
+    }
+
+    #[test]
+    fn test_parse_inline_data() {
+        // [version 1][fixmap 1]["null" -> bin8 [1, 2, 3]]
+        let data = [
+            0x01, 0x81, 0xa4, b'n', b'u', b'l', b'l', 0xc4, 0x03, 1, 2, 3,
+        ];
+        let entries = parse_inline_data(&data).unwrap();
+        assert_eq!(entries, vec![("null".to_string(), vec![1, 2, 3])]);
+
+        assert!(parse_inline_data(&[]).unwrap().is_empty());
+        assert!(parse_inline_data(&[0x02, 0x80]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_huge_counts_without_allocating() {
+        // 4 billion versions announced in a valid, CRC-checked blob
+        let mut data = XL_HEADER.to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&3u16.to_le_bytes());
+        write_blob(&mut data, &[0x03, 0x03, 0xce, 0xff, 0xff, 0xff, 0xff]).unwrap();
+        assert!(parse_full(&data).is_err());
+
+        // map32 of 4 billion inline entries
+        assert!(parse_inline_data(&[0x01, 0xdf, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+
     /// Test computed properties on ObjectMeta.
     #[test]
     fn test_object_meta_computed_properties() {
@@ -0,0 +1,116 @@
+//! End-to-end tests of the `xl-meta` binary
+#![cfg(feature = "serde")]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+const MANY_PARTS: &str = "testdata/xlmeta/xl-many-parts.meta";
+
+fn xl_meta(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xl-meta"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap()
+}
+
+/// Copy the many-parts fixture into `dir` with an inline data section holding
+/// one `key -> data` entry appended
+fn with_inline_entry(dir: &Path, key: &str, data: &[u8]) -> String {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join(MANY_PARTS);
+    let mut xl = std::fs::read(fixture).unwrap();
+    xl.push(0x01); // inline data version
+    xl.push(0x81); // fixmap with one entry
+    xl.push(0xa0 | key.len() as u8);
+    xl.extend_from_slice(key.as_bytes());
+    xl.push(0xc4);
+    xl.push(data.len() as u8);
+    xl.extend_from_slice(data);
+
+    let path = dir.join("xl.meta");
+    std::fs::write(&path, xl).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_json_output_shape() {
+    let out = xl_meta(&[MANY_PARTS]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let json: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let xl = &json[MANY_PARTS];
+    assert_eq!(xl["version"], "1.3");
+    assert_eq!(xl["inline_data"], serde_json::json!([]));
+
+    let versions = xl["versions"].as_array().unwrap();
+    assert_eq!(versions.len(), 7);
+    assert!(versions
+        .iter()
+        .all(|v| v["header"]["version_type"] == "Object"));
+    let v = &versions[0];
+    assert_eq!(
+        v["header"]["version_id"],
+        "8edd0b2c-0888-4a38-8184-9e213b6f92fb"
+    );
+    assert!(v["header"]["signature"].is_string());
+
+    let meta = &v["metadata"];
+    assert_eq!(meta["data_blocks"], 12);
+    assert_eq!(meta["parity_blocks"], 4);
+    assert_eq!(meta["data_dir"], "e17ac3c0-0ff6-4a6a-8d05-b7fe69fe115b");
+    let parts = meta["parts"].as_array().unwrap();
+    assert_eq!(parts.len(), 9016);
+    assert_eq!(parts[0]["number"], 1);
+    assert_eq!(parts[0]["checksum"], "");
+
+    assert_eq!(v["sys_meta"]["X-Minio-Internal-actual-size"], "56720007533");
+}
+
+#[test]
+fn test_export_inline_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = with_inline_entry(dir.path(), "null", b"inline");
+    let export = dir.path().join("export");
+
+    let out = xl_meta(&["--export", export.to_str().unwrap(), &file]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let json: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(
+        json[file.as_str()]["inline_data"],
+        serde_json::json!([{ "version_id": "null", "size": 6 }])
+    );
+    assert_eq!(std::fs::read(export.join("null.data")).unwrap(), b"inline");
+}
+
+#[test]
+fn test_export_rejects_non_uuid_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = with_inline_entry(dir.path(), "../evil", b"inline");
+    let export = dir.path().join("export");
+
+    let out = xl_meta(&["--export", export.to_str().unwrap(), &file]);
+    assert!(!out.status.success());
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("refusing to export"), "{}", stderr);
+    assert!(!dir.path().join("evil.data").exists());
+    assert!(!export.join("../evil.data").exists());
+}
+
+#[test]
+fn test_export_requires_a_directory() {
+    let out = xl_meta(&[MANY_PARTS, "--export"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--export requires a directory"));
+}